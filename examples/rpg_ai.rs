@@ -19,10 +19,11 @@ fn main() -> color_eyre::Result<()> {
     let tools = build_rpg_tools();
     let prompt = r#"
 You are going to play the Tiny CLI RPG using tools only.
-- First, call rpg_get_rules to learn the mechanics.
+- First, call rpg_new_game and pass its game_id to every other rpg_* tool.
+- Call rpg_get_rules to learn the mechanics.
 - Then call rpg_get_state and rpg_list_actions to inspect.
 - Repeatedly call rpg_issue_action until victory or defeat.
-- When the game ends, provide a short summary as the final answer.
+- When the game ends, call rpg_end_game and provide a short summary as the final answer.
 Reply with your final summary as plain text.
 "#;
 
@@ -1,5 +1,7 @@
 //! Example: Manually operate the RPG tools without any LLM
 use rust_test::openai::tools::{
+    RpgSessions,
+    build_rpg_new_game_tool,
     build_rpg_get_rules_tool,
     build_rpg_get_state_tool,
     build_rpg_list_actions_tool,
@@ -11,24 +13,31 @@ fn main() -> color_eyre::Result<()> {
     let _ = dotenvy::dotenv();
     color_eyre::install()?;
 
-    let rules_tool = build_rpg_get_rules_tool();
-    let state_tool = build_rpg_get_state_tool();
-    let list_tool = build_rpg_list_actions_tool();
-    let issue_tool = build_rpg_issue_action_tool();
+    // All tools share one game store; start a seeded game in it
+    let sessions = RpgSessions::new();
+    let new_game_tool = build_rpg_new_game_tool(&sessions);
+    let rules_tool = build_rpg_get_rules_tool(&sessions);
+    let state_tool = build_rpg_get_state_tool(&sessions);
+    let list_tool = build_rpg_list_actions_tool(&sessions);
+    let issue_tool = build_rpg_issue_action_tool(&sessions);
 
-    let rules = rules_tool.execute(&json!({}))?;
+    let game_id = new_game_tool.execute(&json!({"seed": 42}))?["game_id"].clone();
+    println!("Game: {}", game_id);
+    let game = json!({"game_id": game_id});
+
+    let rules = rules_tool.execute(&game)?;
     println!("Rules: {}", rules);
 
     // Loop a few steps: attack until the game ends or after some iterations
     for _ in 0..5 {
-        let state = state_tool.execute(&json!({}))?;
+        let state = state_tool.execute(&game)?;
         println!("State: {}", state);
-        let actions = list_tool.execute(&json!({}))?;
+        let actions = list_tool.execute(&game)?;
         println!("Actions: {}", actions);
 
         // naive policy: if heal available (potions>0 and HP not full), try heal once, otherwise attack
         // For simplicity here we just issue attack
-        let result = issue_tool.execute(&json!({"action": "attack"}))?;
+        let result = issue_tool.execute(&json!({"game_id": game_id, "action": "attack"}))?;
         println!("After action: {}", result);
     }
     Ok(())
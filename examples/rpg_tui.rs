@@ -0,0 +1,25 @@
+//! Example: Play the Tiny RPG in a ratatui screen (A/H/R to act, Q to quit)
+use rust_test::config::Config;
+use rust_test::rpg::tui::{self, RpgTui};
+use rust_test::rpg::Game;
+use rust_test::theme::Theme;
+
+fn main() -> color_eyre::Result<()> {
+    let _ = dotenvy::dotenv();
+    color_eyre::install()?;
+
+    // RPG_SEED=<n> replays the same battles; otherwise a random seed (shown in the inventory panel)
+    let game = match std::env::var("RPG_SEED").ok().and_then(|s| s.parse().ok()) {
+        Some(seed) => match std::env::var("RPG_PLAYER") {
+            Ok(name) => Game::with_seed(seed).with_player_name(name),
+            Err(_) => Game::with_seed(seed),
+        },
+        None => Game::new(),
+    };
+    // Same theme setting as the main TUI (F5 cycles, NO_COLOR disables colors)
+    let mut app = RpgTui::new(game, Theme::from_env(Config::new().theme));
+    let mut terminal = ratatui::init();
+    let result = tui::run(&mut terminal, &mut app);
+    ratatui::restore();
+    Ok(result?)
+}
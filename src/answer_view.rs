@@ -0,0 +1,267 @@
+//! AI回答表示ペイン（折り返し・スクロール・Markdown 描画・マウスでの範囲選択）
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Position, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Widget, Wrap};
+use ratatui::Frame;
+use unicode_width::UnicodeWidthChar;
+
+use crate::theme::Theme;
+use crate::ui::buffer_lines;
+
+/// マウスホイール1刻みでスクロールする行数
+pub const WHEEL_SCROLL_LINES: u16 = 3;
+
+/// 回答ペインのスクロール状態
+///
+/// 折り返し後の行数は描画時にしか分からないため、`render` で最大スクロール量と
+/// 1ページの高さを記録し、次のキー入力ではそれを使ってクランプする。
+#[derive(Debug, Clone, Default)]
+pub struct AnswerView {
+    /// 先頭からのスクロール量（折り返し後の行単位）
+    scroll: u16,
+    /// 直近の描画時点での最大スクロール量
+    max_scroll: u16,
+    /// 直近の描画時点での表示行数
+    page: u16,
+    /// 直近の描画時点での内側の領域（マウス位置の変換に使う）
+    inner: Rect,
+    /// 範囲選択中の折り返し後のテキスト（選択中のみ描画時に更新）
+    wrapped: Vec<String>,
+    /// 選択範囲（起点, 現在位置）
+    selection: Option<(TextPos, TextPos)>,
+}
+
+/// 折り返し後のテキスト上の位置（行, 表示列）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TextPos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl AnswerView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 現在のスクロール量
+    pub fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    /// 先頭に戻す（新しい回答を受け取ったとき）
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+        self.selection = None;
+    }
+
+    /// 画面上の位置が回答の表示領域内か
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        self.inner.contains(Position::new(column, row))
+    }
+
+    /// マウスを押した位置から範囲選択を始める
+    pub fn start_selection(&mut self, column: u16, row: u16) {
+        let pos = self.text_pos(column, row);
+        self.selection = Some((pos, pos));
+    }
+
+    /// ドラッグ中の位置まで選択を広げる。表示領域の上下にはみ出したらスクロールする
+    pub fn extend_selection(&mut self, column: u16, row: u16) {
+        if self.selection.is_none() {
+            return;
+        }
+        if row < self.inner.y {
+            self.scroll_up(1);
+        } else if row >= self.inner.bottom() {
+            self.scroll_down(1);
+        }
+        let row = row.clamp(self.inner.y, self.inner.bottom().saturating_sub(1));
+        let pos = self.text_pos(column, row);
+        if let Some((_, head)) = &mut self.selection {
+            *head = pos;
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some_and(|(a, b)| a != b)
+    }
+
+    /// 選択範囲のテキスト（折り返しの継ぎ目は改行になる）。未選択なら `None`
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.ordered_selection()?;
+        let lines: Vec<String> = (start.line..=end.line)
+            .filter_map(|i| self.wrapped.get(i).map(|line| (i, line)))
+            .map(|(i, line)| {
+                let from = if i == start.line { start.col } else { 0 };
+                let to = if i == end.line { end.col + 1 } else { usize::MAX };
+                slice_columns(line, from, to)
+            })
+            .collect();
+        let text = lines.join("\n");
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// 起点と現在位置を前後順に並べた選択範囲（空なら `None`）
+    fn ordered_selection(&self) -> Option<(TextPos, TextPos)> {
+        let (a, b) = self.selection?;
+        (a != b).then(|| if a <= b { (a, b) } else { (b, a) })
+    }
+
+    /// 画面上の位置を折り返し後のテキスト上の位置に変換
+    fn text_pos(&self, column: u16, row: u16) -> TextPos {
+        TextPos {
+            line: (row.saturating_sub(self.inner.y) + self.scroll) as usize,
+            col: column.saturating_sub(self.inner.x) as usize,
+        }
+    }
+
+    /// `lines` 行だけ上へ
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// `lines` 行だけ下へ
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines).min(self.max_scroll);
+    }
+
+    /// 1ページ上へ（PgUp）
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.page.saturating_sub(1).max(1));
+    }
+
+    /// 1ページ下へ（PgDn）
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.page.saturating_sub(1).max(1));
+    }
+
+    /// 折り返して描画し、スクロール範囲を更新
+    ///
+    /// スクロール可能な場合はタイトルに現在位置（`行/総行数`）を付ける。枠・本文・選択範囲は `theme` の色で描く。
+    pub fn render(&mut self, f: &mut Frame, area: Rect, title: &str, lines: Vec<Line<'static>>, theme: &Theme) {
+        let inner = Block::bordered().inner(area);
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+        let total = paragraph.line_count(inner.width) as u16;
+        self.page = inner.height;
+        self.max_scroll = total.saturating_sub(inner.height);
+        self.scroll = self.scroll.min(self.max_scroll);
+
+        let title = if self.max_scroll > 0 {
+            format!("{title} [{}/{}] PgUp/PgDn", self.scroll + 1, self.max_scroll + 1)
+        } else {
+            title.to_string()
+        };
+        self.inner = inner;
+        if self.selection.is_some() {
+            // 選択テキストを取り出せるように、折り返し後の全行を画面外のバッファに描画しておく
+            let mut offscreen = Buffer::empty(Rect::new(0, 0, inner.width, total.max(1)));
+            paragraph.clone().render(offscreen.area, &mut offscreen);
+            self.wrapped = buffer_lines(&offscreen);
+        }
+        let paragraph = paragraph.block(theme.block(title).style(theme.assistant)).scroll((self.scroll, 0));
+        f.render_widget(paragraph, area);
+        self.highlight_selection(f.buffer_mut(), theme);
+    }
+
+    /// 表示中の選択範囲を強調表示
+    fn highlight_selection(&self, buf: &mut Buffer, theme: &Theme) {
+        let Some((start, end)) = self.ordered_selection() else { return };
+        for y in self.inner.top()..self.inner.bottom() {
+            let line = (y - self.inner.y + self.scroll) as usize;
+            if line < start.line || line > end.line {
+                continue;
+            }
+            let from = if line == start.line { start.col } else { 0 };
+            let to = if line == end.line { end.col + 1 } else { self.inner.width as usize };
+            for x in from..to.min(self.inner.width as usize) {
+                buf[(self.inner.x + x as u16, y)].set_style(theme.highlight);
+            }
+        }
+    }
+}
+
+/// 表示列 `from..to` にかかる文字を取り出す（全角文字は左端の列で判定）
+fn slice_columns(line: &str, from: usize, to: usize) -> String {
+    let mut col = 0;
+    let mut out = String::new();
+    for c in line.chars() {
+        if col >= from && col < to {
+            out.push(c);
+        }
+        col += c.width().unwrap_or(0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn draw(view: &mut AnswerView, lines: usize) {
+        let mut terminal = Terminal::new(TestBackend::new(20, 7)).unwrap();
+        let content: Vec<Line<'static>> = (0..lines).map(|i| Line::from(format!("line {i}"))).collect();
+        terminal.draw(|f| view.render(f, f.area(), "AI Answer", content, &Theme::default())).unwrap();
+    }
+
+    #[test]
+    fn scroll_is_clamped_to_content() {
+        let mut view = AnswerView::new();
+        draw(&mut view, 20); // 表示5行 → 最大15
+        view.page_down();
+        assert_eq!(view.scroll(), 4);
+        view.scroll_down(100);
+        assert_eq!(view.scroll(), 15);
+        view.page_up();
+        assert_eq!(view.scroll(), 11);
+        view.scroll_up(100);
+        assert_eq!(view.scroll(), 0);
+    }
+
+    #[test]
+    fn short_content_does_not_scroll() {
+        let mut view = AnswerView::new();
+        draw(&mut view, 3);
+        view.page_down();
+        assert_eq!(view.scroll(), 0);
+    }
+
+    #[test]
+    fn wrapped_lines_count_toward_scroll() {
+        let mut view = AnswerView::new();
+        let mut terminal = Terminal::new(TestBackend::new(12, 4)).unwrap();
+        // 内側幅10・高さ2。30文字は3行に折り返される
+        terminal.draw(|f| view.render(f, f.area(), "A", vec![Line::from("x".repeat(30))], &Theme::default())).unwrap();
+        view.scroll_down(10);
+        assert_eq!(view.scroll(), 1);
+    }
+
+    #[test]
+    fn drag_selects_wrapped_text() {
+        let mut view = AnswerView::new();
+        let mut terminal = Terminal::new(TestBackend::new(12, 6)).unwrap();
+        let content = || vec![Line::from("hello world"), Line::from("二行目です")];
+        terminal.draw(|f| view.render(f, f.area(), "A", content(), &Theme::default())).unwrap();
+        // 内側は (1,1) から幅10。"hello" / "world" / "二行目です" に折り返される
+        view.start_selection(4, 1);
+        terminal.draw(|f| view.render(f, f.area(), "A", content(), &Theme::default())).unwrap();
+        view.extend_selection(4, 3);
+        assert!(view.has_selection());
+        assert_eq!(view.selected_text().as_deref(), Some("lo\nworld\n二行"));
+
+        // 逆方向のドラッグでも同じ範囲
+        view.start_selection(4, 3);
+        view.extend_selection(4, 1);
+        assert_eq!(view.selected_text().as_deref(), Some("lo\nworld\n二行"));
+
+        view.clear_selection();
+        assert_eq!(view.selected_text(), None);
+    }
+}
@@ -1,27 +1,62 @@
 //! アプリケーション状態管理モジュール
 
+use crate::commands::SlashCommand;
 use crate::config::Config;
-use crate::openai;
-use std::sync::mpsc::{self, Receiver, Sender};
+use crate::input::LineEditor;
+use crate::answer_view::WHEEL_SCROLL_LINES;
+use crate::keymap::{Action, Keymap};
+use crate::layout::{Pane, PaneAreas};
+use crate::markdown::code_blocks;
+use crate::openai::{SessionId, WorkerMessage, WorkerRequest, WorkerSettings};
+use crate::session::Session;
+use crate::theme::Theme;
+use crate::worker::{OpenAiWorker, Worker};
+use crate::ui;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::widgets::Block;
+use std::sync::mpsc::{self, Sender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 use std::time::Instant;
-use tracing::info;
+use tracing::{info, warn};
 
 /// アプリケーションの状態を管理する構造体
 pub struct App {
-    /// 現在の入力テキスト
-    pub input: String,
-    /// 最後に送信されたテキスト
-    pub last_submitted: String,
-    /// AI回答（受信済みの場合）
-    pub ai_answer: Option<String>,
-    /// AI処理中フラグ
-    pub pending: bool,
+    /// 入力欄（カーソル・入力履歴付き。全タブ共通）
+    pub input: LineEditor,
+    /// セッション（タブ）。常に1つ以上
+    pub sessions: Vec<Session>,
+    /// 表示中のセッションの位置
+    pub active: usize,
+    /// 次に開くセッションの ID
+    next_session_id: SessionId,
+    /// 新しいセッションの初期設定
+    default_settings: WorkerSettings,
+    /// 問い合わせ中の送信を待機キューではなく新しいタブで並行実行するか
+    pub parallel_sessions: bool,
+    /// ツール呼び出しインスペクタを表示中か（F2 で切替）
+    pub show_inspector: bool,
+    /// キーバインド（`Config::keymap` から）
+    pub keymap: Keymap,
+    /// 配色テーマ（`Config::theme` から。`toggle_theme` で切り替え）
+    pub theme: Theme,
+    /// キー一覧オーバーレイを表示中か
+    pub show_help: bool,
+    /// 処理中の終了確認ダイアログを表示中か
+    pub confirm_quit: bool,
+    /// スラッシュコマンドの結果やエラー（フッターに表示）
+    pub status: Option<String>,
+    /// 直近の描画時のペイン配置（マウスの当たり判定に使う）
+    pub panes: PaneAreas,
+    /// 最後にコピーしたテキスト（中クリックで入力欄に貼り付け）
+    pub clipboard: Option<String>,
+    /// 端末へ OSC 52 で送るコピー内容（メインループが `take_pending_copy` で取り出して書き出す）
+    pending_copy: Option<String>,
     /// アプリケーション開始時刻
     pub started: Instant,
-    /// プロンプト送信用チャンネル
-    pub tx: Sender<String>,
-    /// AI回答受信用チャンネル
-    pub rx: Receiver<String>,
+    /// プロンプト・設定変更の送信用チャンネル
+    pub tx: Sender<WorkerRequest>,
+    /// AI回答・途中経過イベント受信用チャンネル（メインループが `recv().await` で待つ）
+    pub rx: UnboundedReceiver<WorkerMessage>,
 }
 
 impl App {
@@ -30,25 +65,221 @@ impl App {
         Self::with_config(Config::new())
     }
 
-    /// 設定を指定してアプリケーションインスタンスを作成
+    /// 設定を指定してアプリケーションインスタンスを作成（OpenAI APIワーカーを使う）
     pub fn with_config(config: Config) -> Self {
+        let worker = OpenAiWorker::new(config.clone());
+        Self::with_worker(&config, Box::new(worker))
+    }
+
+    /// ワーカーを指定して作成（テストではエコー / 台本ワーカーを渡す）
+    pub fn with_worker(config: &Config, worker: Box<dyn Worker>) -> Self {
         // プロンプト送信用チャンネル
-        let (tx_prompt, rx_prompt) = mpsc::channel::<String>();
+        let (tx_prompt, rx_prompt) = mpsc::channel::<WorkerRequest>();
         // AI回答受信用チャンネル
-        let (tx_answer, rx_answer) = mpsc::channel::<String>();
+        let (tx_answer, rx_answer) = unbounded_channel::<WorkerMessage>();
+
+        // ワーカーをバックグラウンドで開始
+        worker.start(rx_prompt, tx_answer);
+
+        Self::with_channels(config, tx_prompt, rx_answer)
+    }
 
-        // OpenAI APIワーカーをバックグラウンドで開始
-        openai::start_openai_worker(rx_prompt, tx_answer, config);
+    /// ワーカーとのチャンネルを指定して作成（ワーカーは起動しない）
+    pub fn with_channels(config: &Config, tx: Sender<WorkerRequest>, rx: UnboundedReceiver<WorkerMessage>) -> Self {
+        let keymap = config.keymap.clone();
+        let parallel_sessions = config.parallel_sessions;
+        let default_settings = WorkerSettings::from_config(config);
 
         Self {
-            input: String::new(),
-            last_submitted: String::from("(まだありません)"),
-            ai_answer: None,
-            pending: false,
+            input: LineEditor::new(),
+            sessions: vec![Session::new(0, default_settings.clone())],
+            active: 0,
+            next_session_id: 1,
+            default_settings,
+            parallel_sessions,
+            show_inspector: false,
+            keymap,
+            theme: Theme::from_env(config.theme),
+            show_help: false,
+            confirm_quit: false,
+            status: None,
+            panes: PaneAreas::default(),
+            clipboard: None,
+            pending_copy: None,
             started: Instant::now(),
-            tx: tx_prompt,
-            rx: rx_answer,
+            tx,
+            rx,
+        }
+    }
+
+    /// 表示中のセッション
+    pub fn session(&self) -> &Session {
+        &self.sessions[self.active]
+    }
+
+    /// 表示中のセッション（変更用）
+    pub fn session_mut(&mut self) -> &mut Session {
+        &mut self.sessions[self.active]
+    }
+
+    /// いずれかのセッションが問い合わせ中か（終了確認に使う）
+    pub fn any_pending(&self) -> bool {
+        self.sessions.iter().any(|s| s.pending)
+    }
+
+    /// アクションを適用して状態を更新する（状態の変更はすべてここを通す）
+    ///
+    /// ワーカーへの送信エラーはフッターに表示する。終了する場合は `true` を返す。
+    pub fn update(&mut self, action: Action) -> bool {
+        // 処理中の終了確認: 終了で終了、それ以外で取り消し
+        if self.confirm_quit {
+            self.confirm_quit = false;
+            return action == Action::Quit;
         }
+        // ヘルプ表示中はどのアクションでも閉じる（終了は通す）
+        if self.show_help && action != Action::Quit {
+            self.show_help = false;
+            return false;
+        }
+        match action {
+            Action::Quit if self.any_pending() => self.confirm_quit = true,
+            Action::Quit => return true,
+            Action::Submit => {
+                if let Err(e) = self.submit_prompt() {
+                    self.status = Some(format!("送信エラー: {e}"));
+                }
+            }
+            Action::Cancel => self.clear_input(),
+            Action::ScrollUp => self.session_mut().answer_view.scroll_up(1),
+            Action::ScrollDown => self.session_mut().answer_view.scroll_down(1),
+            Action::PageUp => self.session_mut().answer_view.page_up(),
+            Action::PageDown => self.session_mut().answer_view.page_down(),
+            Action::TogglePanel => self.show_inspector = !self.show_inspector,
+            Action::PanelPrev if self.show_inspector => self.session_mut().inspector.select_prev(),
+            Action::PanelNext if self.show_inspector => self.session_mut().inspector.select_next(),
+            Action::PanelExpand if self.show_inspector => self.session_mut().inspector.toggle_expanded(),
+            Action::PanelPrev | Action::PanelNext | Action::PanelExpand => {}
+            Action::QueuePrev => self.session_mut().queue.select_prev(),
+            Action::QueueNext => self.session_mut().queue.select_next(),
+            Action::QueueEdit => self.edit_queued(),
+            Action::QueueRemove => self.remove_queued(),
+            Action::NewSession => self.new_session(),
+            Action::NextSession => self.next_session(),
+            Action::PrevSession => self.prev_session(),
+            Action::CloseSession => self.close_session(),
+            Action::CopyAnswer => self.copy_answer(),
+            Action::CopyCodeBlock => self.copy_code_block(),
+            Action::ToggleTheme => {
+                self.theme = self.theme.next();
+                let note = if self.theme.color { "" } else { "（NO_COLOR のため色なし）" };
+                self.status = Some(format!("テーマ: {}{note}", self.theme.name));
+            }
+            Action::Help => self.show_help = true,
+        }
+        false
+    }
+
+    /// アクションに当たらないキーを入力欄で処理（カーソル移動・編集・Shift+Enter の改行・入力履歴）
+    pub fn edit_input(&mut self, key: KeyEvent) {
+        self.input.handle_key(key);
+    }
+
+    /// マウス操作を適用する（当たり判定は直近の描画時の `panes` で行う）
+    ///
+    /// 左クリックでタブ切替・入力欄のカーソル移動・待機キューの選択、回答ペインはドラッグで範囲選択して
+    /// 離したときにコピー、中クリックでコピーしたテキストを貼り付け、ホイールはその位置のペインをスクロールする。
+    pub fn mouse(&mut self, mouse: MouseEvent) {
+        let (column, row) = (mouse.column, mouse.row);
+        let pane = self.panes.pane_at(column, row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.session_mut().answer_view.clear_selection();
+                match pane {
+                    Some(Pane::Tabs) => {
+                        if let Some(index) = ui::tab_at(self, column) {
+                            self.active = index;
+                        }
+                    }
+                    Some(Pane::Input) => {
+                        let inner = Block::bordered().inner(self.panes.input);
+                        self.input.click(inner, column, row);
+                    }
+                    Some(Pane::Queue) => {
+                        let inner = Block::bordered().inner(self.panes.queue);
+                        let queue = &mut self.session_mut().queue;
+                        let index = ui::queue_scroll(queue) + row.saturating_sub(inner.y) as usize;
+                        queue.select(index);
+                    }
+                    Some(Pane::Answer) if self.session().answer_view.contains(column, row) => {
+                        self.session_mut().answer_view.start_selection(column, row);
+                    }
+                    _ => {}
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => self.session_mut().answer_view.extend_selection(column, row),
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some(text) = self.session().answer_view.selected_text() {
+                    self.copy(text);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Middle) => {
+                if let Some(text) = self.clipboard.clone() {
+                    self.paste(&text);
+                }
+            }
+            MouseEventKind::ScrollUp if pane == Some(Pane::Inspector) => self.session_mut().inspector.select_prev(),
+            MouseEventKind::ScrollDown if pane == Some(Pane::Inspector) => self.session_mut().inspector.select_next(),
+            MouseEventKind::ScrollUp => self.session_mut().answer_view.scroll_up(WHEEL_SCROLL_LINES),
+            MouseEventKind::ScrollDown => self.session_mut().answer_view.scroll_down(WHEEL_SCROLL_LINES),
+            _ => {}
+        }
+    }
+
+    /// 端末サイズが変わったとき（画面座標が変わるので選択を解除）
+    pub fn resize(&mut self) {
+        for session in &mut self.sessions {
+            session.answer_view.clear_selection();
+        }
+    }
+
+    /// テキストをコピーする（アプリ内と、OSC 52 で端末のクリップボードへ）
+    fn copy(&mut self, text: String) {
+        self.status = Some(format!("{}文字をコピーしました", text.chars().count()));
+        self.clipboard = Some(text.clone());
+        self.pending_copy = Some(text);
+    }
+
+    /// 表示中の回答全体をコピー
+    fn copy_answer(&mut self) {
+        match self.session().ai_answer.clone() {
+            Some(answer) => self.copy(answer),
+            None => self.status = Some("コピーする回答がありません".to_string()),
+        }
+    }
+
+    /// 表示中の回答のコードブロックを、前回コピーしたものの次から順にコピー
+    fn copy_code_block(&mut self) {
+        let blocks = self.session().ai_answer.as_deref().map(code_blocks).unwrap_or_default();
+        if blocks.is_empty() {
+            self.status = Some("回答にコードブロックがありません".to_string());
+            return;
+        }
+        let index = self.session().copied_code_block.map(|i| (i + 1) % blocks.len()).unwrap_or(0);
+        self.session_mut().copied_code_block = Some(index);
+        let block = blocks[index].clone();
+        let chars = block.chars().count();
+        self.copy(block);
+        self.status = Some(format!("コードブロック {}/{} をコピーしました（{chars}文字）", index + 1, blocks.len()));
+    }
+
+    /// 端末のクリップボードへ送るコピー内容を取り出す
+    pub fn take_pending_copy(&mut self) -> Option<String> {
+        self.pending_copy.take()
+    }
+
+    /// ペーストされたテキストを入力欄に挿入
+    pub fn paste(&mut self, text: &str) {
+        self.input.insert_str(text);
     }
 
     /// 入力テキストをクリア
@@ -56,36 +287,193 @@ impl App {
         self.input.clear();
     }
 
-    /// 入力テキストに文字を追加
+    /// カーソル位置に文字を挿入
     pub fn push_char(&mut self, ch: char) {
-        self.input.push(ch);
+        self.input.insert_char(ch);
     }
 
-    /// 入力テキストから最後の文字を削除
+    /// カーソル前の文字を削除
     pub fn pop_char(&mut self) {
-        self.input.pop();
+        self.input.backspace();
+    }
+
+    /// 新しいセッション（タブ）を開いて切り替える
+    pub fn new_session(&mut self) {
+        let id = self.next_session_id;
+        self.next_session_id += 1;
+        self.sessions.push(Session::new(id, self.default_settings.clone()));
+        self.active = self.sessions.len() - 1;
+        info!(target: "app", "session_opened: {}", id);
+    }
+
+    /// 表示中のセッションを閉じる。最後の1つを閉じた場合は新しいセッションを開く
+    ///
+    /// 問い合わせ中でも閉じられる。遅れて届いた回答は捨てる。
+    pub fn close_session(&mut self) {
+        let closed = self.sessions.remove(self.active);
+        info!(target: "app", "session_closed: {}", closed.id);
+        let _ = self.tx.send(WorkerRequest::CloseSession { session: closed.id }); // ワーカー終了時は無視
+        if self.sessions.is_empty() {
+            self.new_session();
+        }
+        self.active = self.active.min(self.sessions.len() - 1);
+    }
+
+    /// 次のタブへ
+    pub fn next_session(&mut self) {
+        self.active = (self.active + 1) % self.sessions.len();
+    }
+
+    /// 前のタブへ
+    pub fn prev_session(&mut self) {
+        self.active = (self.active + self.sessions.len() - 1) % self.sessions.len();
+    }
+
+    /// プロンプトを送信（`/` で始まる入力はスラッシュコマンドとして処理）
+    fn submit_prompt(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parsed) = SlashCommand::parse(self.input.text()) {
+            return self.run_command(parsed);
+        }
+        if self.input.is_empty() {
+            return Ok(());
+        }
+        let prompt = self.input.take();
+        let editing = self.session_mut().editing_queued.take();
+        if self.session().pending {
+            if self.parallel_sessions && editing.is_none() {
+                // 並行実行モード: 新しいタブで同時に問い合わせる
+                self.new_session();
+                return self.send_or_restore(prompt);
+            }
+            // 問い合わせ中は待機キューへ（編集した項目は元の位置へ戻す）
+            let queue = &mut self.session_mut().queue;
+            match editing {
+                Some(index) => queue.insert(index, prompt),
+                None => queue.push(prompt),
+            }
+            self.status = Some(format!("キューに追加しました（{}件待ち）", self.session().queue.len()));
+            return Ok(());
+        }
+        self.send_or_restore(prompt)
+    }
+
+    /// 選択中のセッションから送信し、送れなければプロンプトを入力欄に戻す
+    fn send_or_restore(&mut self, prompt: String) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_prompt(self.active, prompt).map_err(|prompt| {
+            self.input.set_text(prompt);
+            "ワーカーが終了しています".into()
+        })
     }
 
-    /// プロンプトを送信
-    pub fn submit_prompt(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if !self.input.is_empty() && !self.pending {
-            self.last_submitted = self.input.clone();
-            let to_send = self.input.clone();
-            self.clear_input();
-            self.ai_answer = None;
-            self.pending = true;
-            info!(target: "app", "submit_prompt: {}", self.last_submitted);
-            self.tx.send(to_send)?;
+    /// `index` のセッションからプロンプトをワーカーへ送り、処理中の表示に切り替える
+    ///
+    /// 送信できなかった場合は状態を変えず、プロンプトを `Err` で返す。
+    fn send_prompt(&mut self, index: usize, prompt: String) -> Result<(), String> {
+        let id = self.sessions[index].id;
+        info!(target: "app", "submit_prompt[{}]: {}", id, prompt);
+        if self.tx.send(WorkerRequest::Prompt { session: id, prompt: prompt.clone() }).is_err() {
+            warn!(target: "app", "submit_prompt_failed[{}]: worker is gone", id);
+            return Err(prompt);
         }
+        let session = &mut self.sessions[index];
+        session.last_submitted = prompt;
+        session.ai_answer = None;
+        session.inspector.clear();
+        session.pending = true;
+        self.status = None;
         Ok(())
     }
 
-    /// AI回答をチェックして更新
+    /// 選択中の待機プロンプトを入力欄に戻す（入力欄が空のときのみ）
+    pub fn edit_queued(&mut self) {
+        if !self.input.is_empty() {
+            self.status = Some("入力欄を空にしてから編集してください".to_string());
+            return;
+        }
+        if let Some((index, prompt)) = self.session_mut().queue.take_selected() {
+            self.input.set_text(prompt);
+            self.session_mut().editing_queued = Some(index);
+        }
+    }
+
+    /// 選択中の待機プロンプトを削除
+    pub fn remove_queued(&mut self) {
+        if let Some((_, prompt)) = self.session_mut().queue.take_selected() {
+            info!(target: "app", "queued_prompt_removed: {}", prompt);
+            self.status = Some(format!("キューから削除しました（{}件待ち）", self.session().queue.len()));
+        }
+    }
+
+    /// スラッシュコマンドを表示中のセッションの設定に適用してワーカーへ送る。処理中でも次のリクエストから反映される
+    ///
+    /// 解析エラーの場合は入力を残したままエラーを表示する。
+    fn run_command(
+        &mut self,
+        parsed: color_eyre::Result<SlashCommand>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match parsed {
+            Ok(command) => {
+                self.input.take();
+                let session = &mut self.sessions[self.active];
+                let message = command.apply(&mut session.settings);
+                let request = WorkerRequest::UpdateSettings { session: session.id, settings: session.settings.clone() };
+                info!(target: "app", "slash_command[{}]: {}", session.id, message);
+                self.status = Some(message);
+                self.tx.send(request)?;
+            }
+            Err(e) => self.status = Some(format!("エラー: {e}")),
+        }
+        Ok(())
+    }
+
+    /// 届いている AI回答と途中経過イベントをすべて処理
     pub fn check_ai_response(&mut self) {
-        if let Ok(answer) = self.rx.try_recv() {
-            self.ai_answer = Some(answer);
-            self.pending = false;
-            if let Some(ans) = &self.ai_answer { info!(target: "app", "ai_answer_received: {}", ans); }
+        while let Ok(message) = self.rx.try_recv() {
+            self.handle_worker_message(message);
+        }
+    }
+
+    /// ワーカーからのメッセージで宛先のセッションを更新
+    ///
+    /// 閉じたセッション宛てのメッセージは捨てる。
+    pub fn handle_worker_message(&mut self, message: WorkerMessage) {
+        match message {
+            WorkerMessage::Event { session, at, event } => {
+                if let Some(s) = self.sessions.iter_mut().find(|s| s.id == session) {
+                    s.inspector.record(at, &event);
+                }
+            }
+            WorkerMessage::Answer { session, answer } => {
+                info!(target: "app", "ai_answer_received[{}]: {}", session, answer);
+                self.finish_request(session, answer);
+            }
+            WorkerMessage::Failed { session, error } => {
+                warn!(target: "app", "ai_request_failed[{}]: {}", session, error);
+                self.status = Some(format!("エラー: {error}"));
+                self.finish_request(session, format!("エラーが発生しました: {error}"));
+            }
+        }
+    }
+
+    /// 問い合わせの完了を反映し、待機中のプロンプトがあれば続けて送る
+    fn finish_request(&mut self, session: SessionId, answer: String) {
+        let Some(index) = self.sessions.iter().position(|s| s.id == session) else {
+            return;
+        };
+        let s = &mut self.sessions[index];
+        s.ai_answer = Some(answer);
+        s.answer_view.reset();
+        s.copied_code_block = None;
+        s.pending = false;
+        // 待機中のプロンプトがあれば続けて送る（回答は次の送信まで表示したまま）
+        if let Some(next) = s.queue.pop_front() {
+            let answer = s.ai_answer.take();
+            if let Err(next) = self.send_prompt(index, next) {
+                // 送れなかったプロンプトはキューの先頭に戻す
+                self.sessions[index].queue.insert(0, next);
+                self.status = Some("送信エラー: ワーカーが終了しています".to_string());
+            }
+            self.sessions[index].ai_answer = answer;
         }
     }
 
@@ -0,0 +1,198 @@
+//! ヘッドレスCLIモジュール
+//!
+//! サブコマンド無しで起動した場合は従来どおり ratatui UI を起動する。
+//! サブコマンドを指定した場合は UI を使わず、結果を標準出力へ書き出して終了する。
+//!
+//! ```text
+//! rust_test ask "1+1は？"
+//! echo "docs の内容を要約して" | rust_test agent - --tools docs,web --max-loops 8 --format json
+//! rust_test mcp --tools docs,math,rpg
+//! ```
+
+use crate::config::Config;
+use crate::mcp::{start_mcp_tools, McpServer, McpServerConfig};
+use crate::openai::{self, MultiStepAnswer};
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::{eyre::eyre, Result};
+use serde_json::json;
+use std::io::{Read, Write};
+use tracing::info;
+
+/// コマンドライン引数
+#[derive(Debug, Parser)]
+#[command(name = "rust_test", about = "Ratatui + OpenAI demo (サブコマンド無しでTUIを起動)")]
+pub struct Cli {
+    /// 実行するサブコマンド（省略時はTUI）
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+/// ヘッドレスで実行するサブコマンド
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// ツール無しで1回だけ問い合わせる (`get_ai_answer_once`)
+    Ask {
+        /// プロンプト。`-` の場合は標準入力から読み込む
+        prompt: String,
+        /// 出力形式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// ツールを使って多段で回答する (`multi_step_tool_answer`)
+    Agent {
+        /// プロンプト。`-` の場合は標準入力から読み込む
+        prompt: String,
+        /// 使用するツールセット（カンマ区切り: docs, web, math, guess, rpg）
+        #[arg(long, value_delimiter = ',', default_value = "docs,web")]
+        tools: Vec<String>,
+        /// 最大ループ回数
+        #[arg(long, default_value_t = 5)]
+        max_loops: usize,
+        /// 追加で使う外部 MCP サーバー（例: `--mcp "files=npx -y some-mcp-server"`、複数指定可）
+        #[arg(long = "mcp")]
+        mcp_servers: Vec<String>,
+        /// 出力形式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// ツールを MCP サーバーとして標準入出力で公開する
+    Mcp {
+        /// 公開するツールセット（カンマ区切り: docs, web, math, guess, rpg）
+        #[arg(long, value_delimiter = ',', default_value = "docs,web,math,guess,rpg")]
+        tools: Vec<String>,
+    },
+}
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 回答テキストのみ
+    Text,
+    /// JSON（agent の場合はステップも含む）
+    Json,
+}
+
+/// プロンプト引数を解決する（`-` の場合は `input` から全て読み込む）
+pub fn resolve_prompt(arg: &str, input: &mut impl Read) -> Result<String> {
+    let prompt = if arg == "-" {
+        let mut buf = String::new();
+        input.read_to_string(&mut buf)?;
+        buf
+    } else {
+        arg.to_string()
+    };
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(eyre!("prompt is empty"));
+    }
+    Ok(prompt)
+}
+
+/// `ask` の結果を出力用文字列に整形
+pub fn format_answer(answer: &str, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Text => answer.to_string(),
+        OutputFormat::Json => serde_json::to_string_pretty(&json!({ "answer": answer }))?,
+    })
+}
+
+/// `agent` の結果を出力用文字列に整形
+pub fn format_multi_step(answer: &MultiStepAnswer, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Text => answer.final_answer.clone(),
+        OutputFormat::Json => serde_json::to_string_pretty(answer)?,
+    })
+}
+
+/// サブコマンドを実行し、結果を `out` に書き出す
+pub fn run_command(command: CliCommand, config: &Config, out: &mut impl Write) -> Result<()> {
+    let stdin = &mut std::io::stdin();
+    let rendered = match command {
+        CliCommand::Ask { prompt, format } => {
+            let prompt = resolve_prompt(&prompt, stdin)?;
+            info!(target: "app", "cli_ask: {}", prompt);
+            let answer = openai::get_ai_answer_once_blocking(&prompt, config)?;
+            format_answer(&answer, format)?
+        }
+        CliCommand::Agent { prompt, tools, max_loops, mcp_servers, format } => {
+            let prompt = resolve_prompt(&prompt, stdin)?;
+            let mut tools = openai::build_tool_sets(&tools)?;
+            let mut servers = config.mcp_servers.clone();
+            for spec in &mcp_servers {
+                servers.push(McpServerConfig::parse(spec)?);
+            }
+            tools.extend(start_mcp_tools(&servers));
+            info!(target: "app", tools = tools.len(), max_loops, "cli_agent: {}", prompt);
+            let answer = openai::multi_step_tool_answer_blocking(&prompt, &tools, config, Some(max_loops))?;
+            format_multi_step(&answer, format)?
+        }
+        CliCommand::Mcp { tools } => {
+            // 標準出力は JSON-RPC 専用なので、ここでは何も追記しない
+            let tools = openai::build_tool_sets(&tools)?;
+            return McpServer::new(tools).serve_stdio();
+        }
+    };
+    writeln!(out, "{rendered}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai::ToolResolution;
+
+    #[test]
+    fn parse_agent_args() {
+        let cli = Cli::try_parse_from([
+            "rust_test", "agent", "hello", "--tools", "docs,web", "--max-loops", "8", "--format", "json",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(CliCommand::Agent { prompt, tools, max_loops, format, .. }) => {
+                assert_eq!(prompt, "hello");
+                assert_eq!(tools, vec!["docs", "web"]);
+                assert_eq!(max_loops, 8);
+                assert_eq!(format, OutputFormat::Json);
+            }
+            other => panic!("unexpected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_mcp_defaults_to_all_sets() {
+        let cli = Cli::try_parse_from(["rust_test", "mcp"]).unwrap();
+        match cli.command {
+            Some(CliCommand::Mcp { tools }) => assert_eq!(tools, openai::TOOL_SET_NAMES),
+            other => panic!("unexpected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn no_subcommand_means_tui() {
+        let cli = Cli::try_parse_from(["rust_test"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn dash_reads_prompt_from_input() {
+        let mut input = "  from stdin\n".as_bytes();
+        assert_eq!(resolve_prompt("-", &mut input).unwrap(), "from stdin");
+        let mut empty = "".as_bytes();
+        assert!(resolve_prompt("-", &mut empty).is_err());
+    }
+
+    #[test]
+    fn json_output_includes_steps() {
+        let answer = MultiStepAnswer {
+            final_answer: "done".into(),
+            steps: vec![ToolResolution::Executed { name: "add".into(), result: json!({"sum": 3}) }],
+            iterations: 2,
+            truncated: false,
+        };
+        let out = format_multi_step(&answer, OutputFormat::Json).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["final_answer"], "done");
+        assert_eq!(v["steps"][0]["executed"]["name"], "add");
+        assert_eq!(format_multi_step(&answer, OutputFormat::Text).unwrap(), "done");
+    }
+}
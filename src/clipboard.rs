@@ -0,0 +1,45 @@
+//! OSC 52 によるクリップボードへのコピー
+//!
+//! 端末にエスケープシーケンスを送り、端末側でシステムのクリップボードに入れてもらう。
+//! ネイティブのクリップボードライブラリが不要で、SSH 越しでも使える（端末が OSC 52 に対応している場合）。
+
+use std::io::{self, Write};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// `text` をクリップボードに入れる OSC 52 シーケンス
+///
+/// `tmux` が真なら tmux のパススルー（DCS）で包む。
+pub fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if tmux {
+        // パススルー内の ESC は二重にする
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// OSC 52 シーケンスを `out` に書き出す（`TMUX` 環境変数があれば tmux 用に包む）
+pub fn copy_to_clipboard(out: &mut impl Write, text: &str) -> io::Result<()> {
+    let tmux = std::env::var_os("TMUX").is_some();
+    out.write_all(osc52_sequence(text, tmux).as_bytes())?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_text_as_base64() {
+        assert_eq!(osc52_sequence("hello", false), "\x1b]52;c;aGVsbG8=\x07");
+        assert_eq!(osc52_sequence("あ", false), "\x1b]52;c;44GC\x07");
+    }
+
+    #[test]
+    fn tmux_passthrough_doubles_escapes() {
+        assert_eq!(osc52_sequence("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+}
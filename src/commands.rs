@@ -0,0 +1,175 @@
+//! 入力欄のスラッシュコマンド（`/model gpt-4o` など）
+//!
+//! 解析したコマンドを `WorkerSettings` に適用し、App がワーカーへ送る。
+
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+
+use crate::config::DEFAULT_SYSTEM_PROMPT;
+use crate::openai::{WorkerSettings, TOOL_SET_NAMES};
+
+/// 使い方（エラー時に表示）
+pub const SLASH_COMMAND_USAGE: &str =
+    "/model <name> | /maxtokens <n> | /tools [+set] [-set] [set ...] | /maxloops <n> | /system [text]";
+
+/// スラッシュコマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// モデル名を変更
+    Model(String),
+    /// 最大トークン数を変更
+    MaxTokens(u32),
+    /// ツールセットを変更。`replace` があればそれで置き換えた後に `add` / `remove` を適用
+    Tools { replace: Option<Vec<String>>, add: Vec<String>, remove: Vec<String> },
+    /// 最大ループ回数を変更
+    MaxLoops(usize),
+    /// システムプロンプトを変更（`None` で既定に戻す）
+    System(Option<String>),
+}
+
+impl SlashCommand {
+    /// 入力を解析。`/` で始まらない入力は `None`（通常のプロンプト）
+    pub fn parse(input: &str) -> Option<Result<SlashCommand>> {
+        let rest = input.trim().strip_prefix('/')?;
+        let (name, arg) = match rest.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (rest, ""),
+        };
+        Some(Self::parse_command(name, arg))
+    }
+
+    fn parse_command(name: &str, arg: &str) -> Result<SlashCommand> {
+        let require = |what: &str| -> Result<()> {
+            if arg.is_empty() { Err(eyre!("/{name} には {what} が必要です")) } else { Ok(()) }
+        };
+        match name {
+            "model" => {
+                require("モデル名")?;
+                Ok(SlashCommand::Model(arg.to_string()))
+            }
+            "maxtokens" => {
+                require("数値")?;
+                let n: u32 = arg.parse().map_err(|_| eyre!("/maxtokens: `{arg}` は数値ではありません"))?;
+                Ok(SlashCommand::MaxTokens(n))
+            }
+            "maxloops" => {
+                require("数値")?;
+                match arg.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(SlashCommand::MaxLoops(n)),
+                    _ => Err(eyre!("/maxloops: 1 以上の数値を指定してください")),
+                }
+            }
+            "system" => Ok(SlashCommand::System((!arg.is_empty()).then(|| arg.to_string()))),
+            "tools" => {
+                require("ツールセット")?;
+                let (mut replace, mut add, mut remove) = (Vec::new(), Vec::new(), Vec::new());
+                for token in arg.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+                    let (list, set) = match (token.strip_prefix('+'), token.strip_prefix('-')) {
+                        (Some(set), _) => (&mut add, set),
+                        (_, Some(set)) => (&mut remove, set),
+                        _ => (&mut replace, token),
+                    };
+                    if !TOOL_SET_NAMES.contains(&set) {
+                        return Err(eyre!("unknown tool set '{set}' (available: {})", TOOL_SET_NAMES.join(", ")));
+                    }
+                    list.push(set.to_string());
+                }
+                let replace = (!replace.is_empty()).then_some(replace);
+                Ok(SlashCommand::Tools { replace, add, remove })
+            }
+            other => Err(eyre!("unknown command `/{other}` ({SLASH_COMMAND_USAGE})")),
+        }
+    }
+
+    /// 設定に適用し、結果のメッセージを返す
+    pub fn apply(&self, settings: &mut WorkerSettings) -> String {
+        match self {
+            SlashCommand::Model(model) => {
+                settings.model = model.clone();
+                format!("model: {model}")
+            }
+            SlashCommand::MaxTokens(n) => {
+                settings.max_tokens = *n;
+                format!("max_tokens: {n}")
+            }
+            SlashCommand::MaxLoops(n) => {
+                settings.max_loops = *n;
+                format!("maxloops: {n}")
+            }
+            SlashCommand::System(text) => {
+                settings.system_prompt = text.clone().unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
+                match text {
+                    Some(_) => "system prompt を変更しました".to_string(),
+                    None => "system prompt を既定に戻しました".to_string(),
+                }
+            }
+            SlashCommand::Tools { replace, add, remove } => {
+                if let Some(sets) = replace {
+                    settings.tool_sets = sets.clone();
+                }
+                for set in add {
+                    if !settings.tool_sets.contains(set) {
+                        settings.tool_sets.push(set.clone());
+                    }
+                }
+                settings.tool_sets.retain(|s| !remove.contains(s));
+                format!("tools: {}", format_tool_sets(&settings.tool_sets))
+            }
+        }
+    }
+}
+
+/// ツールセット名の表示（空なら `(none)`）
+pub fn format_tool_sets(sets: &[String]) -> String {
+    if sets.is_empty() { "(none)".to_string() } else { sets.join(",") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn parse(input: &str) -> Result<SlashCommand> {
+        SlashCommand::parse(input).expect("slash command")
+    }
+
+    #[test]
+    fn plain_prompt_is_not_a_command() {
+        assert!(SlashCommand::parse("hello /model").is_none());
+    }
+
+    #[test]
+    fn parses_each_command() {
+        assert_eq!(parse("/model gpt-4o").unwrap(), SlashCommand::Model("gpt-4o".into()));
+        assert_eq!(parse("/maxtokens 512").unwrap(), SlashCommand::MaxTokens(512));
+        assert_eq!(parse("/maxloops 10").unwrap(), SlashCommand::MaxLoops(10));
+        assert_eq!(parse("/system 英語で答えて").unwrap(), SlashCommand::System(Some("英語で答えて".into())));
+        assert_eq!(parse("/system").unwrap(), SlashCommand::System(None));
+        assert!(parse("/maxloops 0").is_err());
+        assert!(parse("/model").is_err());
+        assert!(parse("/tools +nope").is_err());
+        assert!(parse("/frobnicate").is_err());
+    }
+
+    #[test]
+    fn tools_add_remove_and_replace() {
+        let mut settings = WorkerSettings::from_config(&Config::new());
+        assert_eq!(settings.tool_sets, vec!["guess"]);
+        parse("/tools +docs -guess +rpg").unwrap().apply(&mut settings);
+        assert_eq!(settings.tool_sets, vec!["docs", "rpg"]);
+        let msg = parse("/tools math,web -web").unwrap().apply(&mut settings);
+        assert_eq!(settings.tool_sets, vec!["math"]);
+        assert_eq!(msg, "tools: math");
+    }
+
+    #[test]
+    fn apply_updates_settings() {
+        let mut settings = WorkerSettings::from_config(&Config::new());
+        parse("/model gpt-4o").unwrap().apply(&mut settings);
+        parse("/system be brief").unwrap().apply(&mut settings);
+        assert_eq!(settings.model, "gpt-4o");
+        assert_eq!(settings.system_prompt, "be brief");
+        parse("/system").unwrap().apply(&mut settings);
+        assert_eq!(settings.system_prompt, DEFAULT_SYSTEM_PROMPT);
+    }
+}
@@ -1,19 +1,53 @@
 //! アプリケーション設定と定数
 
+use color_eyre::Result;
+
+use crate::keymap::Keymap;
+use crate::mcp::McpServerConfig;
+use crate::openai::{ContextBudget, ToolResultLimits};
+use crate::theme::ThemeName;
+
 /// 取得対象の定数 X
 pub const X: i32 = 42;
 
 /// 取得対象の定数 Y
 pub const Y: i32 = 7;
 
+/// 数字あてゲーム（`guess` ツールセット）の正解
+pub const NUMBER_GUESS_TARGET: u32 = 8;
+
+/// 数字あてゲームの上限（1..=この値）
+pub const NUMBER_GUESS_MAX: u32 = 10;
+
+/// キーバインドの上書きファイルを指す環境変数（`Config::from_env` が読む）
+pub const KEYMAP_FILE_ENV: &str = "KEYMAP_FILE";
+
+/// 既定のシステムプロンプト
+pub const DEFAULT_SYSTEM_PROMPT: &str = "あなたは簡潔な日本語で答えるアシスタントです。";
+
 /// アプリケーション設定
+#[derive(Clone)]
 pub struct Config {
     /// OpenAI APIモデル名
     pub model: String,
     /// 最大トークン数
     pub max_tokens: u32,
-    /// イベントポーリング間隔（ミリ秒）
+    /// システムプロンプト
+    pub system_prompt: String,
+    /// 問い合わせ中に経過時間の表示を更新する間隔（ミリ秒）
     pub poll_interval_ms: u64,
+    /// ワーカー起動時に子プロセスとして起動する外部 MCP サーバー
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// ツール結果を履歴へ入れる前に適用するサイズ上限
+    pub tool_result_limits: ToolResultLimits,
+    /// 会話履歴のトークン予算と超過時の縮約戦略
+    pub context_budget: ContextBudget,
+    /// TUI のキーバインド（`Config::from_env` では `KEYMAP_FILE` のファイルで上書き）
+    pub keymap: Keymap,
+    /// 問い合わせ中に送信したプロンプトを待機キューに入れず、新しいタブで並行実行する
+    pub parallel_sessions: bool,
+    /// TUI の配色テーマ（環境変数 `NO_COLOR` があれば色なし）
+    pub theme: ThemeName,
 }
 
 impl Default for Config {
@@ -22,7 +56,14 @@ impl Default for Config {
             model: "gpt-4o-mini".to_string(),
             // NOTE: Keep in sync with tests (tests/config_tests.rs) and design doc.
             max_tokens: 2000,
+            system_prompt: DEFAULT_SYSTEM_PROMPT.to_string(),
             poll_interval_ms: 100,
+            mcp_servers: Vec::new(),
+            tool_result_limits: ToolResultLimits::default(),
+            context_budget: ContextBudget::default(),
+            keymap: Keymap::default(),
+            parallel_sessions: false,
+            theme: ThemeName::default(),
         }
     }
 }
@@ -32,4 +73,21 @@ impl Config {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// 環境変数を反映した設定（TUI 起動時に使う）
+    ///
+    /// `KEYMAP_FILE` が空でなければそのファイルでキーバインドを上書きする。
+    /// ファイルが読めない・書式が誤っている場合は起動前にエラーにする。
+    pub fn from_env() -> Result<Self> {
+        match std::env::var_os(KEYMAP_FILE_ENV).filter(|v| !v.is_empty()) {
+            Some(path) => Self::new().with_keymap_file(path),
+            None => Ok(Self::new()),
+        }
+    }
+
+    /// キーバインドをファイルの上書きを適用したものにする
+    pub fn with_keymap_file(mut self, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        self.keymap = Keymap::from_file(path)?;
+        Ok(self)
+    }
 }
@@ -1,37 +1,52 @@
 //! イベント処理モジュール
+//!
+//! 端末イベントを `Action` に変換して `App::update` に渡す。状態の変更は App が行う。
 
 use crate::app::App;
+use crate::keymap::Action;
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+
+/// キーを `App::keymap` でアクションに変換する。どのアクションにも当たらない場合は `None`
+///
+/// 確認ダイアログ・ヘルプ表示中は、割り当てのないキーも取り消し（`Cancel`）として扱う。
+pub fn action_for_key(app: &App, key: &KeyEvent) -> Option<Action> {
+    let binding = app.keymap.binding_for(key);
+    if app.confirm_quit {
+        if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+            return Some(Action::Quit);
+        }
+        return Some(binding.map(|(_, action)| action).unwrap_or(Action::Cancel));
+    }
+    if app.show_help {
+        return Some(binding.map(|(_, action)| action).unwrap_or(Action::Cancel));
+    }
+    // 修飾なしの文字キー（`?` など）は入力中なら文字として扱う
+    binding.and_then(|(k, action)| (!k.is_plain_char() || app.input.is_empty()).then_some(action))
+}
 
 /// キーイベントを処理
-/// 
+///
 /// # Returns
 /// - `Ok(true)` - アプリケーションを終了
 /// - `Ok(false)` - 処理を継続
 /// - `Err(_)` - エラーが発生
 pub fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
-    match key.code {
-        KeyCode::Esc => return Ok(true),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(true),
-        KeyCode::Enter => {
-            if !app.input.is_empty() && !app.pending {
-                app.last_submitted = app.input.clone();
-                let to_send = app.input.clone();
-                app.input.clear();
-                app.ai_answer = None;
-                app.pending = true;
-                let _ = app.tx.send(to_send); // ワーカーが終了している場合は送信エラーを無視
-            }
-        }
-        KeyCode::Backspace => {
-            app.input.pop();
+    match action_for_key(app, &key) {
+        Some(action) => Ok(app.update(action)),
+        None => {
+            app.edit_input(key);
+            Ok(false)
         }
-        KeyCode::Char(ch) => {
-            // 通常の文字入力
-            app.input.push(ch);
-        }
-        _ => {}
     }
-    Ok(false)
+}
+
+/// マウスイベントを処理（クリック・ドラッグ選択・ホイール）
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    app.mouse(mouse);
+}
+
+/// ペースト（bracketed paste）されたテキストを入力欄に挿入
+pub fn handle_paste(app: &mut App, text: &str) {
+    app.paste(text);
 }
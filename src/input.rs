@@ -0,0 +1,489 @@
+//! 行エディタ（入力欄ウィジェット）モジュール
+//!
+//! `examples/memopad.rs` の文字インデックス方式のカーソル処理を元に、
+//! 単語移動・複数行入力・ペースト・入力履歴・全角文字の表示幅に対応したもの。
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Position, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+use unicode_width::UnicodeWidthChar;
+
+/// 入力履歴の最大保持件数
+const HISTORY_LIMIT: usize = 100;
+
+/// カーソル付きの編集バッファ
+///
+/// カーソル位置はバイトではなく文字インデックスで保持する。
+/// 改行 (`\n`) を含むことで複数行を表す。
+#[derive(Debug, Clone, Default)]
+pub struct LineEditor {
+    /// 編集中のテキスト
+    text: String,
+    /// カーソル位置（文字インデックス）
+    cursor: usize,
+    /// 送信済み入力の履歴（古い順）
+    history: Vec<String>,
+    /// 履歴参照中の位置（`None` は編集中のバッファ）
+    history_index: Option<usize>,
+    /// 履歴参照を始める前の編集中テキスト
+    draft: String,
+}
+
+impl LineEditor {
+    /// 空のエディタを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 現在のテキスト
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// カーソル位置（文字インデックス）
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// テキストが空か
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// 送信済み入力の履歴（古い順）
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// テキストを置き換え、カーソルを末尾へ移動
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.char_len();
+    }
+
+    /// テキストをクリア
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.history_index = None;
+    }
+
+    /// テキストを取り出して履歴に追加し、バッファを空にする（送信時に使用）
+    pub fn take(&mut self) -> String {
+        let text = std::mem::take(&mut self.text);
+        self.cursor = 0;
+        self.history_index = None;
+        self.draft.clear();
+        if !text.trim().is_empty() && self.history.last() != Some(&text) {
+            self.history.push(text.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
+        text
+    }
+
+    /// カーソル位置に1文字挿入
+    pub fn insert_char(&mut self, ch: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, ch);
+        self.cursor += 1;
+    }
+
+    /// カーソル位置に文字列を挿入（ペースト用。`\r\n` / `\r` は `\n` に正規化）
+    pub fn insert_str(&mut self, s: &str) {
+        let normalized = s.replace("\r\n", "\n").replace('\r', "\n");
+        let at = self.byte_index(self.cursor);
+        self.text.insert_str(at, &normalized);
+        self.cursor += normalized.chars().count();
+    }
+
+    /// 改行を挿入
+    pub fn newline(&mut self) {
+        self.insert_char('\n');
+    }
+
+    /// カーソル前の1文字を削除
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// カーソル位置の1文字を削除
+    pub fn delete(&mut self) {
+        if self.cursor < self.char_len() {
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// カーソル前の1単語を削除
+    pub fn delete_word_back(&mut self) {
+        let start = self.prev_word_start();
+        let (from, to) = (self.byte_index(start), self.byte_index(self.cursor));
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+    }
+
+    /// 1文字左へ
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// 1文字右へ
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.char_len());
+    }
+
+    /// 前の単語の先頭へ
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.prev_word_start();
+    }
+
+    /// 次の単語の末尾へ
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = self.cursor;
+        while i < chars.len() && !is_word_char(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && is_word_char(chars[i]) {
+            i += 1;
+        }
+        self.cursor = i;
+    }
+
+    /// 現在行の先頭へ
+    pub fn move_home(&mut self) {
+        let (row, _) = self.row_col();
+        self.cursor = self.line_start(row);
+    }
+
+    /// 現在行の末尾へ
+    pub fn move_end(&mut self) {
+        let (row, _) = self.row_col();
+        self.cursor = self.line_start(row) + self.line_chars(row);
+    }
+
+    /// 上へ移動。先頭行にいる場合は入力履歴の1つ前を表示
+    pub fn move_up(&mut self) {
+        let (row, col) = self.row_col();
+        if row == 0 {
+            self.history_prev();
+        } else {
+            self.cursor = self.line_start(row - 1) + col.min(self.line_chars(row - 1));
+        }
+    }
+
+    /// 下へ移動。最終行にいる場合は入力履歴の1つ後を表示
+    pub fn move_down(&mut self) {
+        let (row, col) = self.row_col();
+        if row + 1 >= self.line_count() {
+            self.history_next();
+        } else {
+            self.cursor = self.line_start(row + 1) + col.min(self.line_chars(row + 1));
+        }
+    }
+
+    /// 履歴の1つ前（古い方）を表示
+    pub fn history_prev(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let next = match self.history_index {
+            None => {
+                self.draft = self.text.clone();
+                self.history.len() - 1
+            }
+            Some(0) => 0,
+            Some(i) => i - 1,
+        };
+        self.history_index = Some(next);
+        self.set_text(self.history[next].clone());
+    }
+
+    /// 履歴の1つ後（新しい方）を表示。末尾を越えると編集中のテキストに戻る
+    pub fn history_next(&mut self) {
+        let Some(i) = self.history_index else { return };
+        if i + 1 < self.history.len() {
+            self.history_index = Some(i + 1);
+            self.set_text(self.history[i + 1].clone());
+        } else {
+            self.history_index = None;
+            let draft = std::mem::take(&mut self.draft);
+            self.set_text(draft);
+        }
+    }
+
+    /// 編集キーを処理
+    ///
+    /// # Returns
+    /// - `true` - キーを消費した
+    /// - `false` - エディタの対象外（Enter 単体などは呼び出し側で処理）
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            // Shift+Enter を報告しない端末向けに Alt+Enter も改行として扱う
+            KeyCode::Enter if key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) => self.newline(),
+            KeyCode::Left if ctrl || alt => self.move_word_left(),
+            KeyCode::Right if ctrl || alt => self.move_word_right(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            KeyCode::Backspace if ctrl || alt => self.delete_word_back(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char('a') if ctrl => self.move_home(),
+            KeyCode::Char('e') if ctrl => self.move_end(),
+            KeyCode::Char('w') if ctrl => self.delete_word_back(),
+            KeyCode::Char('b') if alt => self.move_word_left(),
+            KeyCode::Char('f') if alt => self.move_word_right(),
+            // 修飾キー付きの制御は除外（Shift のみ許可）
+            KeyCode::Char(c) if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT => self.insert_char(c),
+            _ => return false,
+        }
+        true
+    }
+
+    /// 表示行数（改行数 + 1）
+    pub fn line_count(&self) -> usize {
+        self.text.split('\n').count()
+    }
+
+    /// カーソルの表示位置 (列, 行)。列は全角文字を2として数える表示幅
+    pub fn cursor_display_position(&self) -> (u16, u16) {
+        let (row, col) = self.row_col();
+        let line = self.text.split('\n').nth(row).unwrap_or("");
+        let width: usize = line.chars().take(col).map(char_width).sum();
+        (width as u16, row as u16)
+    }
+
+    /// 内側の領域 `inner` に描画するときのスクロール量 (行, 列)
+    fn scroll_offset(&self, inner: Rect) -> (u16, u16) {
+        let (x, y) = self.cursor_display_position();
+        ((y + 1).saturating_sub(inner.height), (x + 1).saturating_sub(inner.width))
+    }
+
+    /// 画面上の位置 (`column`, `row`) をクリックしたときにカーソルを移動する
+    ///
+    /// `inner` は描画時の内側の領域。行末より右をクリックした場合は行末に置く。
+    pub fn click(&mut self, inner: Rect, column: u16, row: u16) {
+        let (scroll_y, scroll_x) = self.scroll_offset(inner);
+        let target_row = (row.saturating_sub(inner.y) + scroll_y) as usize;
+        let target_x = (column.saturating_sub(inner.x) + scroll_x) as usize;
+        let row = target_row.min(self.line_count() - 1);
+        let line = self.text.split('\n').nth(row).unwrap_or("");
+        let mut width = 0;
+        let mut col = 0;
+        // クリックしたセルを含む文字の前に置く
+        for c in line.chars() {
+            if width + char_width(c) > target_x {
+                break;
+            }
+            width += char_width(c);
+            col += 1;
+        }
+        self.cursor = self.line_start(row) + col;
+    }
+
+    /// 枠付きで描画し、端末カーソルを編集位置に置く
+    ///
+    /// 内側の領域に収まらない場合はカーソルが見えるように縦横にスクロールする。
+    pub fn render(&self, f: &mut Frame, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        let (x, y) = self.cursor_display_position();
+        let (scroll_y, scroll_x) = self.scroll_offset(inner);
+        let lines: Vec<Line> = self.text.split('\n').map(Line::raw).collect();
+        let widget = Paragraph::new(lines).block(block).scroll((scroll_y, scroll_x));
+        f.render_widget(widget, area);
+        if inner.width > 0 && inner.height > 0 {
+            f.set_cursor_position(Position::new(inner.x + x - scroll_x, inner.y + y - scroll_y));
+        }
+    }
+
+    fn char_len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// 文字インデックスをバイト位置に変換
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text.char_indices().nth(char_index).map(|(b, _)| b).unwrap_or(self.text.len())
+    }
+
+    /// カーソルの (行, 行内の文字インデックス)
+    fn row_col(&self) -> (usize, usize) {
+        let before: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let row = before.iter().filter(|&&c| c == '\n').count();
+        let col = before.iter().rev().take_while(|&&c| c != '\n').count();
+        (row, col)
+    }
+
+    /// `row` 行目の先頭の文字インデックス
+    fn line_start(&self, row: usize) -> usize {
+        self.text.split('\n').take(row).map(|l| l.chars().count() + 1).sum()
+    }
+
+    /// `row` 行目の文字数
+    fn line_chars(&self, row: usize) -> usize {
+        self.text.split('\n').nth(row).map(|l| l.chars().count()).unwrap_or(0)
+    }
+
+    fn prev_word_start(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = self.cursor;
+        while i > 0 && !is_word_char(chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word_char(chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 1文字の表示幅（制御文字は0、全角は2）
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn insert_and_delete_at_cursor() {
+        let mut e = LineEditor::new();
+        for c in "hllo".chars() {
+            e.insert_char(c);
+        }
+        e.move_home();
+        e.move_right();
+        e.insert_char('e');
+        assert_eq!(e.text(), "hello");
+        assert_eq!(e.cursor(), 2);
+        e.backspace();
+        e.delete();
+        assert_eq!(e.text(), "hlo");
+        e.move_end();
+        e.delete(); // 末尾では何もしない
+        assert_eq!(e.text(), "hlo");
+    }
+
+    #[test]
+    fn multibyte_chars_use_char_indices() {
+        let mut e = LineEditor::new();
+        e.set_text("日本語");
+        e.move_left();
+        e.backspace();
+        assert_eq!(e.text(), "日語");
+        assert_eq!(e.cursor_display_position(), (2, 0));
+        e.move_end();
+        assert_eq!(e.cursor_display_position(), (4, 0));
+    }
+
+    #[test]
+    fn word_jumps_and_delete_word() {
+        let mut e = LineEditor::new();
+        e.set_text("foo bar_baz  qux");
+        e.move_word_left();
+        assert_eq!(e.cursor(), 13);
+        e.move_word_left();
+        assert_eq!(e.cursor(), 4);
+        e.move_word_right();
+        assert_eq!(e.cursor(), 11);
+        e.move_end();
+        e.delete_word_back();
+        assert_eq!(e.text(), "foo bar_baz  ");
+    }
+
+    #[test]
+    fn shift_enter_and_vertical_movement() {
+        let mut e = LineEditor::new();
+        e.set_text("abcd");
+        assert!(e.handle_key(key(KeyCode::Enter, KeyModifiers::SHIFT)));
+        e.insert_str("xy");
+        assert_eq!(e.text(), "abcd\nxy");
+        assert_eq!(e.line_count(), 2);
+        assert!(!e.handle_key(key(KeyCode::Enter, KeyModifiers::NONE)));
+
+        e.move_up();
+        assert_eq!(e.cursor(), 2);
+        e.move_end();
+        e.move_down();
+        assert_eq!(e.cursor(), 7); // 2行目は2文字なので末尾へ丸める
+        assert_eq!(e.cursor_display_position(), (2, 1));
+    }
+
+    #[test]
+    fn click_moves_cursor_to_cell() {
+        let mut ed = LineEditor::new();
+        ed.insert_str("abc\nあいう");
+        let inner = Rect::new(1, 1, 20, 3);
+        ed.click(inner, 3, 1);
+        assert_eq!(ed.cursor(), 2);
+        // 全角「い」の右半分 → 「い」の前
+        ed.click(inner, 4, 2);
+        assert_eq!(ed.cursor(), 5);
+        // 行末より右と最終行より下
+        ed.click(inner, 19, 5);
+        assert_eq!(ed.cursor(), 7);
+    }
+
+    #[test]
+    fn paste_normalizes_newlines() {
+        let mut e = LineEditor::new();
+        e.set_text("[]");
+        e.move_left();
+        e.insert_str("a\r\nb\rc");
+        assert_eq!(e.text(), "[a\nb\nc]");
+        assert_eq!(e.cursor(), 6);
+    }
+
+    #[test]
+    fn history_navigation_restores_draft() {
+        let mut e = LineEditor::new();
+        e.set_text("first");
+        assert_eq!(e.take(), "first");
+        e.set_text("second");
+        e.take();
+        e.set_text("second");
+        e.take(); // 直前と同じ入力は重複登録しない
+        assert_eq!(e.history(), ["first", "second"]);
+
+        e.set_text("draft");
+        e.move_up();
+        assert_eq!(e.text(), "second");
+        e.move_up();
+        e.move_up();
+        assert_eq!(e.text(), "first");
+        e.move_down();
+        assert_eq!(e.text(), "second");
+        e.move_down();
+        assert_eq!(e.text(), "draft");
+    }
+
+    #[test]
+    fn ctrl_chars_are_not_inserted() {
+        let mut e = LineEditor::new();
+        assert!(!e.handle_key(key(KeyCode::Char('x'), KeyModifiers::CONTROL)));
+        assert!(e.handle_key(key(KeyCode::Char('A'), KeyModifiers::SHIFT)));
+        assert_eq!(e.text(), "A");
+    }
+}
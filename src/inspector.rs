@@ -0,0 +1,322 @@
+//! ツール呼び出しインスペクタ（多段推論のタイムライン表示パネル）
+//!
+//! ワーカーから転送された `MultiStepLogEvent` を反復ごとのエントリにまとめ、
+//! ツール名・引数・結果 JSON（折りたたみ可）・所要時間・エラーを表示する。
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use serde_json::Value;
+
+use crate::openai::{MultiStepLogEvent, ToolCallDecision, ToolResolution};
+use crate::theme::Theme;
+
+/// エントリの状態
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryStatus {
+    /// ツール実行中（Resolved 待ち）
+    Running,
+    /// 実行成功
+    Ok,
+    /// ツール未発見・引数パース失敗・実行エラー
+    Error(String),
+    /// 最終回答（ツール呼び出しなし）
+    Final,
+    /// 付随情報（コンテキスト縮約・打ち切りなど）
+    Info,
+}
+
+/// タイムラインの1エントリ
+#[derive(Debug, Clone)]
+pub struct InspectorEntry {
+    pub iteration: Option<usize>,
+    /// ツール名または見出し
+    pub title: String,
+    /// 呼び出し引数（生の JSON 文字列）
+    pub arguments: Option<String>,
+    pub result: Option<Value>,
+    pub status: EntryStatus,
+    /// 結果サイズ制限などの補足
+    pub notes: Vec<String>,
+    pub started: Instant,
+    pub duration: Option<Duration>,
+}
+
+impl InspectorEntry {
+    fn new(iteration: Option<usize>, title: impl Into<String>, status: EntryStatus, at: Instant) -> Self {
+        Self {
+            iteration,
+            title: title.into(),
+            arguments: None,
+            result: None,
+            status,
+            notes: Vec::new(),
+            started: at,
+            duration: None,
+        }
+    }
+}
+
+/// インスペクタパネルの状態
+#[derive(Debug, Clone, Default)]
+pub struct ToolInspector {
+    entries: Vec<InspectorEntry>,
+    selected: usize,
+    /// 結果 JSON を展開しているエントリ
+    expanded: HashSet<usize>,
+}
+
+impl ToolInspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[InspectorEntry] {
+        &self.entries
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// 新しいプロンプトの送信時にタイムラインをクリア
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.expanded.clear();
+        self.selected = 0;
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+    }
+
+    /// 選択中エントリの結果 JSON の展開/折りたたみ
+    pub fn toggle_expanded(&mut self) {
+        if !self.expanded.remove(&self.selected) {
+            self.expanded.insert(self.selected);
+        }
+    }
+
+    pub fn is_expanded(&self, index: usize) -> bool {
+        self.expanded.contains(&index)
+    }
+
+    /// ワーカーからのイベントを取り込む（`at` はイベント発生時刻）
+    pub fn record(&mut self, at: Instant, event: &MultiStepLogEvent) {
+        match event {
+            MultiStepLogEvent::Proposed { iteration, decision: ToolCallDecision::ToolCall { name, arguments } } => {
+                let mut entry = InspectorEntry::new(Some(*iteration), name.clone(), EntryStatus::Running, at);
+                entry.arguments = Some(arguments.clone());
+                self.push(entry);
+            }
+            MultiStepLogEvent::Resolved { resolution, .. } => {
+                if let Some(entry) = self.running_mut() {
+                    entry.duration = Some(at.saturating_duration_since(entry.started));
+                    match resolution {
+                        ToolResolution::Executed { result, .. } => {
+                            entry.status = EntryStatus::Ok;
+                            entry.result = Some(result.clone());
+                        }
+                        ToolResolution::ToolNotFound { requested } => {
+                            entry.status = EntryStatus::Error(format!("tool not found: {requested}"));
+                        }
+                        ToolResolution::ArgumentsParseError { error, .. } => {
+                            entry.status = EntryStatus::Error(format!("arguments parse error: {error}"));
+                        }
+                        ToolResolution::ExecutionError { error, .. } => {
+                            entry.status = EntryStatus::Error(format!("execution error: {error}"));
+                        }
+                        ToolResolution::ModelText(text) => {
+                            entry.status = EntryStatus::Ok;
+                            entry.result = Some(Value::String(text.clone()));
+                        }
+                    }
+                }
+            }
+            MultiStepLogEvent::ToolResultLimited { name, original_bytes, limited_bytes, strategy, .. } => {
+                if let Some(entry) = self.entries.iter_mut().rev().find(|e| &e.title == name) {
+                    entry.notes.push(format!("result {strategy}: {original_bytes} -> {limited_bytes} bytes"));
+                }
+            }
+            MultiStepLogEvent::ContextTrimmed { iteration, removed, excerpted, tokens_before, tokens_after } => {
+                let what = if *excerpted { "excerpted" } else { "dropped" };
+                let title = format!("context trimmed: {removed} messages {what} ({tokens_before} -> {tokens_after} tokens)");
+                self.push(InspectorEntry::new(Some(*iteration), title, EntryStatus::Info, at));
+            }
+            MultiStepLogEvent::FinalText { iteration, text } => {
+                let title = format!("final answer ({} chars)", text.chars().count());
+                self.push(InspectorEntry::new(Some(*iteration), title, EntryStatus::Final, at));
+            }
+            MultiStepLogEvent::Truncated { max_loops } => {
+                let title = format!("stopped after max loops ({max_loops})");
+                self.push(InspectorEntry::new(None, title, EntryStatus::Info, at));
+            }
+            // IterationStart / Proposed(Text) / HistoryFunctionAppended / EarlyFailure は他のイベントで表現済み
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, entry: InspectorEntry) {
+        // 末尾を選択していたら新しいエントリに追従
+        let follow = self.entries.is_empty() || self.selected + 1 == self.entries.len();
+        self.entries.push(entry);
+        if follow {
+            self.selected = self.entries.len() - 1;
+        }
+    }
+
+    fn running_mut(&mut self) -> Option<&mut InspectorEntry> {
+        self.entries.iter_mut().rev().find(|e| e.status == EntryStatus::Running)
+    }
+
+    /// タイムラインの表示行と、選択中エントリの先頭行番号
+    fn lines(&self, theme: &Theme) -> (Vec<Line<'static>>, usize) {
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i == self.selected {
+                selected_line = lines.len();
+            }
+            let expanded = self.is_expanded(i);
+            let (marker, style) = match &entry.status {
+                EntryStatus::Running => ("…", theme.warning),
+                EntryStatus::Ok => (if expanded { "▾" } else { "▸" }, theme.tool),
+                EntryStatus::Error(_) => ("✗", theme.error),
+                EntryStatus::Final => ("✓", theme.title),
+                EntryStatus::Info => ("·", theme.muted),
+            };
+            let mut header = vec![
+                Span::styled(format!("{marker} "), style),
+                Span::styled(entry.iteration.map(|n| format!("#{n} ")).unwrap_or_default(), theme.muted),
+                Span::styled(entry.title.clone(), style.add_modifier(Modifier::BOLD)),
+            ];
+            if let Some(d) = entry.duration {
+                header.push(Span::styled(format!("  {}", format_duration(d)), theme.muted));
+            }
+            let mut header = Line::from(header);
+            if i == self.selected {
+                header = header.patch_style(theme.highlight);
+            }
+            lines.push(header);
+
+            if let Some(args) = &entry.arguments {
+                lines.push(Line::from(format!("    args: {args}")));
+            }
+            if let EntryStatus::Error(error) = &entry.status {
+                lines.push(Line::styled(format!("    {error}"), theme.error));
+            }
+            for note in &entry.notes {
+                lines.push(Line::styled(format!("    {note}"), theme.warning));
+            }
+            if expanded && let Some(result) = &entry.result {
+                let pretty = serde_json::to_string_pretty(result).unwrap_or_else(|_| result.to_string());
+                lines.extend(pretty.lines().map(|l| Line::styled(format!("    {l}"), theme.assistant)));
+            }
+        }
+        (lines, selected_line)
+    }
+
+    /// パネルを描画。選択中エントリが見えるようにスクロールする
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        let block = theme.block("Tool Calls");
+        let inner_height = block.inner(area).height as usize;
+        let (lines, selected_line) = if self.entries.is_empty() {
+            (vec![Line::styled("(ツール呼び出しはまだありません)", theme.muted)], 0)
+        } else {
+            self.lines(theme)
+        };
+        let scroll = (selected_line + 1).saturating_sub(inner_height) as u16;
+        f.render_widget(Paragraph::new(lines).block(block).scroll((scroll, 0)), area);
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    if d < Duration::from_secs(1) {
+        format!("{}ms", d.as_millis())
+    } else {
+        format!("{:.1}s", d.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_call(iteration: usize, name: &str) -> MultiStepLogEvent {
+        MultiStepLogEvent::Proposed {
+            iteration,
+            decision: ToolCallDecision::ToolCall { name: name.into(), arguments: "{\"x\":1}".into() },
+        }
+    }
+
+    #[test]
+    fn records_timeline_with_durations_and_errors() {
+        let t0 = Instant::now();
+        let mut inspector = ToolInspector::new();
+        inspector.record(t0, &MultiStepLogEvent::IterationStart { iteration: 1 });
+        inspector.record(t0, &tool_call(1, "add"));
+        assert_eq!(inspector.entries()[0].status, EntryStatus::Running);
+        inspector.record(
+            t0 + Duration::from_millis(25),
+            &MultiStepLogEvent::Resolved {
+                iteration: 1,
+                resolution: ToolResolution::Executed { name: "add".into(), result: json!({"sum": 3}) },
+            },
+        );
+        inspector.record(t0, &tool_call(2, "explode"));
+        inspector.record(
+            t0 + Duration::from_millis(5),
+            &MultiStepLogEvent::Resolved {
+                iteration: 2,
+                resolution: ToolResolution::ExecutionError { name: "explode".into(), error: "boom".into() },
+            },
+        );
+
+        let entries = inspector.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status, EntryStatus::Ok);
+        assert_eq!(entries[0].duration, Some(Duration::from_millis(25)));
+        assert_eq!(entries[0].result, Some(json!({"sum": 3})));
+        assert_eq!(entries[1].status, EntryStatus::Error("execution error: boom".into()));
+        assert_eq!(inspector.selected(), 1);
+    }
+
+    #[test]
+    fn expanding_shows_pretty_json() {
+        let t0 = Instant::now();
+        let mut inspector = ToolInspector::new();
+        inspector.record(t0, &tool_call(1, "add"));
+        inspector.record(
+            t0,
+            &MultiStepLogEvent::Resolved {
+                iteration: 1,
+                resolution: ToolResolution::Executed { name: "add".into(), result: json!({"sum": 3}) },
+            },
+        );
+        let collapsed = inspector.lines(&Theme::default()).0.len();
+        inspector.toggle_expanded();
+        let (lines, _) = inspector.lines(&Theme::default());
+        assert_eq!(lines.len(), collapsed + 3); // "{", "\"sum\": 3", "}"
+        assert!(lines.iter().any(|l| l.to_string().contains("\"sum\": 3")));
+    }
+
+    #[test]
+    fn clear_resets_selection() {
+        let mut inspector = ToolInspector::new();
+        inspector.record(Instant::now(), &MultiStepLogEvent::Truncated { max_loops: 3 });
+        inspector.toggle_expanded();
+        inspector.clear();
+        assert!(inspector.entries().is_empty());
+        assert!(!inspector.is_expanded(0));
+    }
+}
@@ -0,0 +1,441 @@
+//! キーバインド設定モジュール
+//!
+//! キーイベントを名前付きのアクションに対応付ける。既定値は `Keymap::default()`、
+//! 上書きは `Keymap::with_overrides` に `action = key, key` 形式のテキストを渡す。
+//! TUI 起動時は環境変数 `KEYMAP_FILE` が指すファイルから読み込む（`Config::from_env`）。
+
+use std::fmt::{self, Display};
+use std::path::Path;
+
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// キーに割り当てられる操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// プロンプトを送信
+    Submit,
+    /// オーバーレイを閉じる / 入力をクリア
+    Cancel,
+    /// アプリケーションを終了（処理中は確認あり）
+    Quit,
+    /// 回答を1行上へ
+    ScrollUp,
+    /// 回答を1行下へ
+    ScrollDown,
+    /// 回答を1ページ上へ
+    PageUp,
+    /// 回答を1ページ下へ
+    PageDown,
+    /// ツール呼び出しパネルの表示切替
+    TogglePanel,
+    /// パネルの前のエントリを選択
+    PanelPrev,
+    /// パネルの次のエントリを選択
+    PanelNext,
+    /// パネルの選択エントリの結果を展開/折りたたみ
+    PanelExpand,
+    /// 待機キューの前の項目を選択
+    QueuePrev,
+    /// 待機キューの次の項目を選択
+    QueueNext,
+    /// 選択中の待機プロンプトを入力欄に戻して編集
+    QueueEdit,
+    /// 選択中の待機プロンプトを削除
+    QueueRemove,
+    /// 新しいセッション（タブ）を開く
+    NewSession,
+    /// 次のセッションへ切り替え
+    NextSession,
+    /// 前のセッションへ切り替え
+    PrevSession,
+    /// 表示中のセッションを閉じる
+    CloseSession,
+    /// 回答全体をクリップボードにコピー
+    CopyAnswer,
+    /// 回答のコードブロックを順にクリップボードにコピー
+    CopyCodeBlock,
+    /// 配色テーマを切り替え
+    ToggleTheme,
+    /// キー一覧の表示切替
+    Help,
+}
+
+impl Action {
+    /// 全アクション（ヘルプの表示順）
+    pub const ALL: [Action; 23] = [
+        Action::Submit,
+        Action::Cancel,
+        Action::Quit,
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::TogglePanel,
+        Action::PanelPrev,
+        Action::PanelNext,
+        Action::PanelExpand,
+        Action::QueuePrev,
+        Action::QueueNext,
+        Action::QueueEdit,
+        Action::QueueRemove,
+        Action::NewSession,
+        Action::NextSession,
+        Action::PrevSession,
+        Action::CloseSession,
+        Action::CopyAnswer,
+        Action::CopyCodeBlock,
+        Action::ToggleTheme,
+        Action::Help,
+    ];
+
+    /// 設定で使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Submit => "submit",
+            Action::Cancel => "cancel",
+            Action::Quit => "quit",
+            Action::ScrollUp => "scroll_up",
+            Action::ScrollDown => "scroll_down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::TogglePanel => "toggle_panel",
+            Action::PanelPrev => "panel_prev",
+            Action::PanelNext => "panel_next",
+            Action::PanelExpand => "panel_expand",
+            Action::QueuePrev => "queue_prev",
+            Action::QueueNext => "queue_next",
+            Action::QueueEdit => "queue_edit",
+            Action::QueueRemove => "queue_remove",
+            Action::NewSession => "new_session",
+            Action::NextSession => "next_session",
+            Action::PrevSession => "prev_session",
+            Action::CloseSession => "close_session",
+            Action::CopyAnswer => "copy_answer",
+            Action::CopyCodeBlock => "copy_code_block",
+            Action::ToggleTheme => "toggle_theme",
+            Action::Help => "help",
+        }
+    }
+
+    /// ヘルプに表示する説明
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Submit => "送信",
+            Action::Cancel => "閉じる / 入力をクリア",
+            Action::Quit => "終了",
+            Action::ScrollUp => "回答を1行上へ",
+            Action::ScrollDown => "回答を1行下へ",
+            Action::PageUp => "回答を1ページ上へ",
+            Action::PageDown => "回答を1ページ下へ",
+            Action::TogglePanel => "ツール呼び出しパネル",
+            Action::PanelPrev => "パネル: 前のエントリ",
+            Action::PanelNext => "パネル: 次のエントリ",
+            Action::PanelExpand => "パネル: 結果の展開",
+            Action::QueuePrev => "待機キュー: 前を選択",
+            Action::QueueNext => "待機キュー: 次を選択",
+            Action::QueueEdit => "待機キュー: 入力欄に戻して編集",
+            Action::QueueRemove => "待機キュー: 削除",
+            Action::NewSession => "新しいタブ",
+            Action::NextSession => "次のタブ",
+            Action::PrevSession => "前のタブ",
+            Action::CloseSession => "タブを閉じる",
+            Action::CopyAnswer => "回答をコピー",
+            Action::CopyCodeBlock => "コードブロックを順にコピー",
+            Action::ToggleTheme => "テーマ切り替え",
+            Action::Help => "このヘルプ",
+        }
+    }
+
+    /// 名前からアクションを取得
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 修飾キー付きのキー1つ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// `ctrl+c`, `shift+enter`, `f2`, `pgup`, `?` のような表記を解析
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        // `ctrl++` のように `+` 自体もキーとして書ける
+        let (mods, key) = if let Some(mods) = spec.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            match spec.rfind('+') {
+                Some(i) if i + 1 < spec.len() => (&spec[..i], &spec[i + 1..]),
+                _ => ("", spec),
+            }
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => return Err(eyre!("unknown modifier `{other}` in key `{spec}`")),
+            };
+        }
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" => KeyCode::PageUp,
+            "pgdn" | "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            f if f.len() > 1 && f.starts_with('f') && f[1..].chars().all(|c| c.is_ascii_digit()) => {
+                KeyCode::F(f[1..].parse().map_err(|_| eyre!("invalid function key `{key}`"))?)
+            }
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(if modifiers.is_empty() { c } else { c.to_ascii_lowercase() }),
+                    _ => return Err(eyre!("unknown key `{key}`")),
+                }
+            }
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// キーイベントがこのバインドに一致するか
+    ///
+    /// 文字キーは端末によって Shift の有無が揺れる（`?` など）ため Shift を無視して比較する。
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        match (self.code, key.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                let strip = |m: KeyModifiers| m - KeyModifiers::SHIFT;
+                let same_char = if self.modifiers.is_empty() { a == b } else { a.eq_ignore_ascii_case(&b) };
+                same_char && strip(self.modifiers) == strip(key.modifiers)
+            }
+            (a, b) => a == b && self.modifiers == key.modifiers,
+        }
+    }
+
+    /// 修飾なしの文字キーか（入力欄が空でないときは文字入力を優先する）
+    pub fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && (self.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (m, name) in [(KeyModifiers::CONTROL, "Ctrl+"), (KeyModifiers::ALT, "Alt+"), (KeyModifiers::SHIFT, "Shift+")] {
+            if self.modifiers.contains(m) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("BackTab"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Delete"),
+            KeyCode::Insert => f.write_str("Insert"),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{c}"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// キー → アクションの対応表
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let defaults: [(&str, Action); 28] = [
+            ("enter", Action::Submit),
+            ("esc", Action::Cancel),
+            ("ctrl+c", Action::Quit),
+            ("ctrl+q", Action::Quit),
+            ("shift+up", Action::ScrollUp),
+            ("shift+down", Action::ScrollDown),
+            ("pgup", Action::PageUp),
+            ("pgdn", Action::PageDown),
+            ("f2", Action::TogglePanel),
+            ("ctrl+up", Action::PanelPrev),
+            ("ctrl+down", Action::PanelNext),
+            ("ctrl+o", Action::PanelExpand),
+            ("alt+up", Action::QueuePrev),
+            ("alt+down", Action::QueueNext),
+            ("alt+e", Action::QueueEdit),
+            ("alt+d", Action::QueueRemove),
+            ("ctrl+n", Action::NewSession),
+            ("ctrl+t", Action::NewSession),
+            ("ctrl+pgdn", Action::NextSession),
+            ("f4", Action::NextSession),
+            ("ctrl+pgup", Action::PrevSession),
+            ("f3", Action::PrevSession),
+            ("alt+w", Action::CloseSession),
+            ("ctrl+y", Action::CopyAnswer),
+            ("alt+y", Action::CopyCodeBlock),
+            ("f5", Action::ToggleTheme),
+            ("f1", Action::Help),
+            ("?", Action::Help),
+        ];
+        let bindings = defaults
+            .into_iter()
+            .map(|(spec, action)| (KeyBinding::parse(spec).expect("valid default key"), action))
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// 空の対応表
+    pub fn empty() -> Self {
+        Self { bindings: Vec::new() }
+    }
+
+    /// `key` を `action` に割り当てる（同じキーの既存の割り当ては置き換え）
+    pub fn bind(mut self, key: KeyBinding, action: Action) -> Self {
+        self.bindings.retain(|(k, _)| *k != key);
+        self.bindings.push((key, action));
+        self
+    }
+
+    /// `action = key, key` 形式の行で既定値を上書きする
+    ///
+    /// 記載されたアクションは既定のキーがすべて外れ、指定したキーだけになる。
+    /// 空行と `#` から始まる行は無視する。
+    pub fn with_overrides(mut self, text: &str) -> Result<Self> {
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| eyre!("line {}: expected `action = key`", lineno + 1))?;
+            let action = Action::from_name(name.trim())
+                .ok_or_else(|| eyre!("line {}: unknown action `{}`", lineno + 1, name.trim()))?;
+            self.bindings.retain(|(_, a)| *a != action);
+            for spec in keys.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let key = KeyBinding::parse(spec).map_err(|e| eyre!("line {}: {e}", lineno + 1))?;
+                self = self.bind(key, action);
+            }
+        }
+        Ok(self)
+    }
+
+    /// ファイルに書かれた上書きを既定値に適用する
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).wrap_err_with(|| format!("reading keymap file {}", path.display()))?;
+        Self::default()
+            .with_overrides(&text)
+            .wrap_err_with(|| format!("invalid keymap file {}", path.display()))
+    }
+
+    /// キーイベントに対応するアクション
+    pub fn action_for(&self, key: &KeyEvent) -> Option<Action> {
+        self.binding_for(key).map(|(_, a)| a)
+    }
+
+    /// キーイベントに一致するバインドとアクション
+    pub fn binding_for(&self, key: &KeyEvent) -> Option<(KeyBinding, Action)> {
+        self.bindings.iter().find(|(k, _)| k.matches(key)).copied()
+    }
+
+    /// アクションに割り当てられたキー
+    pub fn keys_for(&self, action: Action) -> Vec<KeyBinding> {
+        self.bindings.iter().filter(|(_, a)| *a == action).map(|(k, _)| *k).collect()
+    }
+
+    /// アクションのキー表記（複数は ` / ` 区切り、未割り当ては `-`）
+    pub fn describe(&self, action: Action) -> String {
+        let keys = self.keys_for(action);
+        if keys.is_empty() {
+            return "-".to_string();
+        }
+        keys.iter().map(ToString::to_string).collect::<Vec<_>>().join(" / ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for (spec, shown) in [("ctrl+c", "Ctrl+C"), ("shift+enter", "Shift+Enter"), ("f2", "F2"), ("pgup", "PgUp"), ("?", "?"), ("ctrl++", "Ctrl++")] {
+            assert_eq!(KeyBinding::parse(spec).unwrap().to_string(), shown, "{spec}");
+        }
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("nope").is_err());
+    }
+
+    #[test]
+    fn defaults_map_expected_actions() {
+        let km = Keymap::default();
+        assert_eq!(km.action_for(&ev(KeyCode::Enter, KeyModifiers::NONE)), Some(Action::Submit));
+        assert_eq!(km.action_for(&ev(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(km.action_for(&ev(KeyCode::Esc, KeyModifiers::NONE)), Some(Action::Cancel));
+        // `?` は Shift 付きで届く端末もある
+        assert_eq!(km.action_for(&ev(KeyCode::Char('?'), KeyModifiers::SHIFT)), Some(Action::Help));
+        assert_eq!(km.action_for(&ev(KeyCode::Enter, KeyModifiers::SHIFT)), None);
+        assert_eq!(km.action_for(&ev(KeyCode::Char('c'), KeyModifiers::NONE)), None);
+        assert_eq!(km.action_for(&ev(KeyCode::PageDown, KeyModifiers::CONTROL)), Some(Action::NextSession));
+        assert_eq!(km.action_for(&ev(KeyCode::Char('w'), KeyModifiers::ALT)), Some(Action::CloseSession));
+    }
+
+    #[test]
+    fn overrides_replace_defaults_for_listed_actions() {
+        let km = Keymap::default()
+            .with_overrides("# comment\nquit = ctrl+x\nsubmit = ctrl+s, ctrl+enter\n")
+            .unwrap();
+        assert_eq!(km.action_for(&ev(KeyCode::Char('c'), KeyModifiers::CONTROL)), None);
+        assert_eq!(km.action_for(&ev(KeyCode::Char('x'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(km.describe(Action::Submit), "Ctrl+S / Ctrl+Enter");
+        assert_eq!(km.describe(Action::Help), "F1 / ?");
+
+        assert!(Keymap::default().with_overrides("fly = f5").is_err());
+        assert!(Keymap::default().with_overrides("quit ctrl+x").is_err());
+    }
+}
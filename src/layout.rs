@@ -0,0 +1,207 @@
+//! 端末サイズに応じたペイン配置
+//!
+//! 狭い・低い端末ではヘッダーと直近送信を畳み、インスペクタは回答ペインと入れ替える。
+//! 広い端末では入力系と回答を左右に並べる。計算結果はマウス操作の当たり判定にも使う。
+
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+
+/// これより狭い端末はコンパクト表示
+pub const COMPACT_MAX_WIDTH: u16 = 60;
+/// これより低い端末はコンパクト表示
+pub const COMPACT_MAX_HEIGHT: u16 = 20;
+/// これ以上広い端末は左右分割表示
+pub const WIDE_MIN_WIDTH: u16 = 140;
+
+/// ヘッダー（枠 + 3行）の高さ
+const HEADER_HEIGHT: u16 = 5;
+/// 直近送信（枠 + 1行）の高さ
+const LAST_SUBMITTED_HEIGHT: u16 = 3;
+
+/// 配置の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// ヘッダー・直近送信を畳み、インスペクタは回答の代わりに表示
+    Compact,
+    /// 縦に積む（インスペクタは右側）
+    Normal,
+    /// 入力系を左、回答を右に並べる（インスペクタはさらに右）
+    Wide,
+}
+
+impl LayoutMode {
+    pub fn for_area(area: Rect) -> Self {
+        if area.width < COMPACT_MAX_WIDTH || area.height < COMPACT_MAX_HEIGHT {
+            LayoutMode::Compact
+        } else if area.width >= WIDE_MIN_WIDTH {
+            LayoutMode::Wide
+        } else {
+            LayoutMode::Normal
+        }
+    }
+}
+
+/// 各ペインの領域（表示しないペインは幅・高さ 0）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaneAreas {
+    pub tabs: Rect,
+    pub header: Rect,
+    pub input: Rect,
+    pub last_submitted: Rect,
+    pub queue: Rect,
+    pub answer: Rect,
+    pub inspector: Rect,
+    pub footer: Rect,
+}
+
+/// マウスの当たり判定で使うペインの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Tabs,
+    Header,
+    Input,
+    LastSubmitted,
+    Queue,
+    Answer,
+    Inspector,
+    Footer,
+}
+
+impl PaneAreas {
+    /// 画面上の位置にあるペイン
+    pub fn pane_at(&self, column: u16, row: u16) -> Option<Pane> {
+        let position = Position::new(column, row);
+        [
+            (self.tabs, Pane::Tabs),
+            (self.header, Pane::Header),
+            (self.input, Pane::Input),
+            (self.last_submitted, Pane::LastSubmitted),
+            (self.queue, Pane::Queue),
+            (self.answer, Pane::Answer),
+            (self.inspector, Pane::Inspector),
+            (self.footer, Pane::Footer),
+        ]
+        .into_iter()
+        .find(|(rect, _)| rect.contains(position))
+        .map(|(_, pane)| pane)
+    }
+}
+
+/// ペインの配置を計算する
+///
+/// `input_height` / `queue_height` は枠込みの高さ（待機キューが空なら 0）。
+pub fn compute(area: Rect, input_height: u16, queue_height: u16, show_inspector: bool) -> PaneAreas {
+    let mode = LayoutMode::for_area(area);
+    let mut panes = PaneAreas::default();
+    let [tabs, body, footer] = Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(area);
+    panes.tabs = tabs;
+    panes.footer = footer;
+
+    match mode {
+        LayoutMode::Compact => {
+            let [input, queue, main] = Layout::vertical([
+                Constraint::Length(input_height),
+                Constraint::Length(queue_height),
+                Constraint::Min(0),
+            ])
+            .areas(body);
+            panes.input = input;
+            panes.queue = queue;
+            if show_inspector {
+                panes.inspector = main;
+            } else {
+                panes.answer = main;
+            }
+        }
+        LayoutMode::Normal => {
+            let left = if show_inspector {
+                let [left, right] = split_horizontal(body, 60);
+                panes.inspector = right;
+                left
+            } else {
+                body
+            };
+            let [header, input, last, queue, answer] = Layout::vertical([
+                Constraint::Length(HEADER_HEIGHT),
+                Constraint::Length(input_height),
+                Constraint::Length(LAST_SUBMITTED_HEIGHT),
+                Constraint::Length(queue_height),
+                Constraint::Min(3),
+            ])
+            .areas(left);
+            (panes.header, panes.input, panes.last_submitted, panes.queue, panes.answer) = (header, input, last, queue, answer);
+        }
+        LayoutMode::Wide => {
+            let (side, main) = if show_inspector {
+                let [side, main, inspector] =
+                    Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(40), Constraint::Percentage(25)]).areas(body);
+                panes.inspector = inspector;
+                (side, main)
+            } else {
+                let [side, main] = split_horizontal(body, 40);
+                (side, main)
+            };
+            let [header, input, last, queue, _] = Layout::vertical([
+                Constraint::Length(HEADER_HEIGHT),
+                Constraint::Length(input_height),
+                Constraint::Length(LAST_SUBMITTED_HEIGHT),
+                Constraint::Length(queue_height),
+                Constraint::Min(0),
+            ])
+            .areas(side);
+            (panes.header, panes.input, panes.last_submitted, panes.queue) = (header, input, last, queue);
+            panes.answer = main;
+        }
+    }
+    panes
+}
+
+/// 左 `left_percent`% と残りに分ける
+fn split_horizontal(area: Rect, left_percent: u16) -> [Rect; 2] {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(left_percent), Constraint::Percentage(100 - left_percent)])
+        .split(area);
+    [chunks[0], chunks[1]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_follows_terminal_size() {
+        assert_eq!(LayoutMode::for_area(Rect::new(0, 0, 50, 40)), LayoutMode::Compact);
+        assert_eq!(LayoutMode::for_area(Rect::new(0, 0, 100, 15)), LayoutMode::Compact);
+        assert_eq!(LayoutMode::for_area(Rect::new(0, 0, 80, 24)), LayoutMode::Normal);
+        assert_eq!(LayoutMode::for_area(Rect::new(0, 0, 160, 40)), LayoutMode::Wide);
+    }
+
+    #[test]
+    fn compact_collapses_header_and_swaps_inspector() {
+        let panes = compute(Rect::new(0, 0, 50, 16), 3, 0, false);
+        assert!(panes.header.is_empty());
+        assert!(panes.last_submitted.is_empty());
+        assert_eq!(panes.answer.height, 16 - 1 - 1 - 3);
+
+        let panes = compute(Rect::new(0, 0, 50, 16), 3, 0, true);
+        assert!(panes.answer.is_empty());
+        assert_eq!(panes.inspector.height, 11);
+    }
+
+    #[test]
+    fn wide_puts_answer_beside_input() {
+        let panes = compute(Rect::new(0, 0, 160, 40), 3, 0, false);
+        assert_eq!(panes.answer.x, panes.input.right());
+        assert_eq!(panes.answer.height, 38);
+        assert_eq!(panes.pane_at(panes.answer.x + 1, 10), Some(Pane::Answer));
+        assert_eq!(panes.pane_at(1, 0), Some(Pane::Tabs));
+    }
+
+    #[test]
+    fn normal_matches_stacked_layout() {
+        let panes = compute(Rect::new(0, 0, 80, 24), 3, 0, false);
+        assert_eq!((panes.header.y, panes.input.y, panes.last_submitted.y, panes.answer.y), (1, 6, 9, 12));
+        assert!(panes.queue.is_empty());
+        assert_eq!(panes.footer.y, 23);
+    }
+}
@@ -1,10 +1,24 @@
 
 // 同階層のファイルをモジュールとしてインポート
+pub mod answer_view; // scrollable, markdown-rendered answer pane
 pub mod app;
+pub mod cli;
+pub mod clipboard; // OSC 52 copy to the system clipboard
+pub mod commands; // slash commands typed into the input box
 pub mod config;
 pub mod event;
+pub mod input; // line editor widget for the input box
+pub mod inspector; // tool-call timeline panel
+pub mod keymap; // key -> action bindings
+pub mod layout; // responsive pane placement and mouse hit-testing
+pub mod markdown; // markdown -> ratatui lines
+pub mod mcp; // MCP (Model Context Protocol) stdio server / client
 pub mod openai;
+pub mod queue; // prompts waiting while a request is in flight
+pub mod session; // per-tab state for the multi-session workspace
+pub mod theme; // color themes (dark / light / high-contrast, NO_COLOR)
 pub mod ui;
+pub mod worker; // swappable backends behind App (OpenAI / echo / scripted)
 pub mod sqlite; // SQLite utilities
 pub mod rpg; // Tiny RPG library (rules/models/game/ui) for AI tools
 
@@ -13,9 +27,15 @@ pub use config::Config;
 pub use sqlite::Db;
 
 use color_eyre::Result;
-use crossterm::event::{self as crossterm_event, Event, KeyEventKind};
-use ratatui::DefaultTerminal;
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, EventStream,
+    KeyEventKind,
+};
+use futures::{Stream, StreamExt};
+use ratatui::backend::Backend;
+use ratatui::{DefaultTerminal, Terminal};
 use std::time::Duration;
+use tokio::time::MissedTickBehavior;
 
 // Ensure .env is loaded for tests before anything else runs in the test process.
 #[cfg(test)]
@@ -25,29 +45,66 @@ fn load_dotenv_for_tests() {
 }
 
 /// アプリケーションのメインループを実行
-pub fn run(mut terminal: DefaultTerminal) -> Result<()> {
-    let mut app = App::new();
+pub fn run(mut terminal: DefaultTerminal, config: Config) -> Result<()> {
+    let tick = Duration::from_millis(config.poll_interval_ms);
+    let mut app = App::with_config(config);
+    // ペーストを1つのイベントとして受け取る（改行で送信されないように）。マウスはクリック・選択・ホイール用
+    crossterm::execute!(std::io::stdout(), EnableBracketedPaste, EnableMouseCapture)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let res = runtime.block_on(run_loop(&mut terminal, &mut app, EventStream::new(), tick));
+    crossterm::execute!(std::io::stdout(), DisableBracketedPaste, DisableMouseCapture)?;
+    res.map(|_| ())
+}
 
+/// 端末イベント・ワーカーからのメッセージ・タイマーを1つのループで待ち、状態が変わったときだけ再描画する
+///
+/// タイマー（`tick` 間隔）は問い合わせ中の経過時間表示の更新用で、待機中は再描画しない。
+/// 描画した回数を返す（テストで不要な再描画がないことを確かめる）。
+pub async fn run_loop<B, S>(terminal: &mut Terminal<B>, app: &mut App, mut events: S, tick: Duration) -> Result<usize>
+where
+    B: Backend,
+    S: Stream<Item = std::io::Result<Event>> + Unpin,
+{
+    let mut ticker = tokio::time::interval(tick);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut frames = 0;
+    let mut dirty = true;
     loop {
-        // AI回答の非ブロッキングチェック
-        app.check_ai_response();
-        
-        terminal.draw(|f| ui::render(f, &app))?;
-
-        // 100ms以内にイベントが来たら処理
-        if crossterm_event::poll(Duration::from_millis(100))? {
-            match crossterm_event::read()? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    if event::handle_key(&mut app, key)? {
-                        break; // trueの場合終了
+        if dirty {
+            terminal.draw(|f| ui::render(f, app))?;
+            frames += 1;
+        }
+        dirty = tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                match event? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        if event::handle_key(app, key)? {
+                            break; // trueの場合終了
+                        }
                     }
+                    Event::Paste(text) => event::handle_paste(app, &text),
+                    Event::Mouse(mouse) => event::handle_mouse(app, mouse),
+                    Event::Resize(_, _) => {
+                        // 配置は次の描画で端末サイズから計算し直す
+                        terminal.autoresize()?;
+                        app.resize();
+                    }
+                    _ => continue,
                 }
-                Event::Resize(_, _) => {
-                    // 次ループで再描画されるので特別な処理なし
+                // コピーは OSC 52 で端末のクリップボードへ
+                if let Some(text) = app.take_pending_copy() {
+                    clipboard::copy_to_clipboard(&mut std::io::stdout(), &text)?;
                 }
-                _ => {}
+                true
             }
-        }
+            Some(message) = app.rx.recv() => {
+                app.handle_worker_message(message);
+                app.check_ai_response(); // 同時に届いた分もまとめて反映
+                true
+            }
+            _ = ticker.tick() => app.any_pending(),
+        };
     }
-    Ok(())
+    Ok(frames)
 }
@@ -1,4 +1,6 @@
+use clap::Parser;
 use color_eyre::Result;
+use rust_test::cli::{self, Cli};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use tracing_appender::rolling;
 
@@ -32,8 +34,18 @@ fn main() -> Result<()> {
 
     // guard を使うことでdropされないようにする
     let _keep_guard = guard;
+
+    // サブコマンド指定時はTUIを起動せずヘッドレスで実行
+    let args = Cli::parse();
+    if let Some(command) = args.command {
+        let config = rust_test::Config::new();
+        return cli::run_command(command, &config, &mut std::io::stdout().lock());
+    }
+
+    // キーマップの誤りは端末を切り替える前に報告する
+    let config = rust_test::Config::from_env()?;
     let terminal = ratatui::init();
-    let res = rust_test::run(terminal);
+    let res = rust_test::run(terminal, config);
     ratatui::restore();
     res
 }
@@ -0,0 +1,290 @@
+//! Markdown → ratatui `Line` 変換モジュール
+//!
+//! モデルの回答はほとんど Markdown で返るため、見出し・リスト・コードブロック・
+//! 太字・斜体・インラインコード・引用を端末向けのスタイルに変換する。
+//! 表や画像など未対応の要素はテキストのみ表示する。
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme::Theme;
+
+/// Markdown テキストを表示用の行に変換（コード・見出し・リンク・記号の色はテーマから取る）
+pub fn render_markdown(text: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let mut r = Renderer::new(theme);
+    for event in Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH) {
+        r.event(event);
+    }
+    r.finish()
+}
+
+/// コードブロックの中身を出現順に取り出す（コピー用。末尾の改行は除く）
+pub fn code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for event in Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => current = Some(String::new()),
+            Event::Text(t) if current.is_some() => current.as_mut().unwrap().push_str(&t),
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(block) = current.take() {
+                    blocks.push(block.trim_end_matches('\n').to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// リストのネスト状態（番号付きなら次の番号）
+struct ListState {
+    next_number: Option<u64>,
+}
+
+struct Renderer {
+    theme: Theme,
+    lines: Vec<Line<'static>>,
+    /// 組み立て中の行
+    current: Vec<Span<'static>>,
+    /// 強調などのスタイルスタック
+    styles: Vec<Style>,
+    lists: Vec<ListState>,
+    quote_depth: usize,
+    in_code_block: bool,
+}
+
+impl Renderer {
+    fn new(theme: &Theme) -> Self {
+        Self {
+            theme: *theme,
+            lines: Vec::new(),
+            current: Vec::new(),
+            styles: Vec::new(),
+            lists: Vec::new(),
+            quote_depth: 0,
+            in_code_block: false,
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles.iter().fold(Style::default(), |acc, s| acc.patch(*s))
+    }
+
+    fn prefix(&self) -> String {
+        "│ ".repeat(self.quote_depth)
+    }
+
+    fn push_span(&mut self, text: String, style: Style) {
+        if self.current.is_empty() && self.quote_depth > 0 {
+            self.current.push(Span::styled(self.prefix(), self.theme.marker));
+        }
+        self.current.push(Span::styled(text, style));
+    }
+
+    /// 組み立て中の行を確定
+    fn flush(&mut self) {
+        if !self.current.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.current)));
+        }
+    }
+
+    /// ブロック要素の区切り（空行は連続させない）
+    fn blank(&mut self) {
+        self.flush();
+        if self.lists.is_empty() && self.lines.last().is_some_and(|l| l.width() > 0) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                for line in text.trim_end_matches('\n').split('\n') {
+                    self.lines.push(Line::from(vec![
+                        Span::raw(format!("{}  ", self.prefix())),
+                        Span::styled(line.to_string(), self.theme.code),
+                    ]));
+                }
+            }
+            Event::Text(text) => {
+                let style = self.style();
+                self.push_span(text.into_string(), style);
+            }
+            Event::Code(code) => self.push_span(code.into_string(), self.theme.code),
+            Event::SoftBreak => {
+                let style = self.style();
+                self.push_span(" ".into(), style);
+            }
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.blank();
+                self.lines.push(Line::styled("─".repeat(20), self.theme.marker));
+            }
+            Event::TaskListMarker(done) => self.push_span(if done { "[x] " } else { "[ ] " }.into(), Style::default()),
+            Event::Html(html) | Event::InlineHtml(html) => self.push_span(html.into_string(), Style::default()),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph => {}
+            Tag::Heading { level, .. } => {
+                self.blank();
+                let style = match level {
+                    HeadingLevel::H1 => self.theme.heading.add_modifier(Modifier::UNDERLINED),
+                    HeadingLevel::H2 => self.theme.heading,
+                    _ => Style::new().add_modifier(Modifier::BOLD),
+                };
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.blank();
+                self.quote_depth += 1;
+                self.styles.push(Style::new().add_modifier(Modifier::ITALIC));
+            }
+            Tag::CodeBlock(kind) => {
+                self.blank();
+                self.in_code_block = true;
+                if let CodeBlockKind::Fenced(lang) = kind
+                    && !lang.is_empty()
+                {
+                    self.lines.push(Line::styled(format!("{}  {lang}", self.prefix()), self.theme.marker));
+                }
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.blank();
+                } else {
+                    self.flush();
+                }
+                self.lists.push(ListState { next_number: start });
+            }
+            Tag::Item => {
+                self.flush();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(ListState { next_number: Some(n) }) => {
+                        *n += 1;
+                        format!("{indent}{}. ", *n - 1)
+                    }
+                    _ => format!("{indent}• "),
+                };
+                let style = self.theme.marker;
+                self.push_span(marker, style);
+            }
+            Tag::Emphasis => self.styles.push(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.styles.push(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.styles.push(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { .. } => self.styles.push(self.theme.link),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.blank(),
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.blank();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.styles.pop();
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.blank();
+            }
+            TagEnd::CodeBlock => {
+                self.in_code_block = false;
+                self.blank();
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                self.blank();
+            }
+            TagEnd::Item => self.flush(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.styles.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line<'static>> {
+        self.flush();
+        while self.lines.last().is_some_and(|l| l.width() == 0) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    fn plain(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect()).collect()
+    }
+
+    #[test]
+    fn headings_paragraphs_and_emphasis() {
+        let lines = render_markdown("# Title\n\nSome **bold** and *it*.\nnext", &Theme::default());
+        assert_eq!(plain(&lines), vec!["Title", "", "Some bold and it. next"]);
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        let bold = lines[2].spans.iter().find(|s| s.content == "bold").unwrap();
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        let it = lines[2].spans.iter().find(|s| s.content == "it").unwrap();
+        assert!(it.style.add_modifier.contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn code_blocks_are_extracted_in_order() {
+        let md = "text\n\n```rust\nfn a() {}\n```\n\n    indented\n\n```\nb\nc\n```\n";
+        assert_eq!(code_blocks(md), vec!["fn a() {}", "indented", "b\nc"]);
+        assert!(code_blocks("no code `inline`").is_empty());
+    }
+
+    #[test]
+    fn lists_are_bulleted_and_numbered() {
+        let lines = render_markdown("- a\n- b\n  - nested\n\n3. x\n4. y", &Theme::default());
+        assert_eq!(plain(&lines), vec!["• a", "• b", "  • nested", "", "3. x", "4. y"]);
+    }
+
+    #[test]
+    fn code_blocks_use_code_style() {
+        let lines = render_markdown("text\n\n```rust\nfn main() {}\nlet x = 1;\n```\nafter `inline`", &Theme::default());
+        assert_eq!(plain(&lines), vec!["text", "", "  rust", "  fn main() {}", "  let x = 1;", "", "after inline"]);
+        let code = Theme::default().code;
+        assert_eq!(lines[3].spans[1].style, code);
+        assert_eq!(lines[6].spans[1].style, code);
+    }
+
+    #[test]
+    fn styles_follow_the_theme() {
+        let md = "## Head
+
+- [link](https://example.com)
+
+`code`";
+        for theme in ThemeName::ALL.map(Theme::new).into_iter().chain([Theme::default().without_color()]) {
+            let lines = render_markdown(md, &theme);
+            assert_eq!(lines[0].spans[0].style, theme.heading);
+            assert_eq!(lines[2].spans[0].style, theme.marker);
+            assert_eq!(lines[2].spans[1].style, theme.link);
+            assert_eq!(lines[4].spans[0].style, theme.code);
+        }
+    }
+
+    #[test]
+    fn plain_text_passes_through() {
+        assert_eq!(plain(&render_markdown("日本語の回答です", &Theme::default())), vec!["日本語の回答です"]);
+        assert!(render_markdown("", &Theme::default()).is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+//! MCP client: start an external MCP server as a child process and use its tools.
+//!
+//! Each remote tool is wrapped as a `ToolDefinition` whose handler forwards the call over
+//! stdio JSON-RPC (`tools/call`), so the multi-step loop can use third-party tools alongside
+//! the built-in ones. Remote tools are exposed as `<server>__<tool>` so they can never shadow
+//! a built-in tool or another server's tool of the same name. Names are capped at
+//! `MAX_TOOL_NAME_LEN`; when two capped names collide, each gets a hash suffix of its original.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use color_eyre::{eyre::{eyre, WrapErr}, Result};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::openai::tools::ToolDefinition;
+use super::protocol::{JsonRpcRequest, JsonRpcResponse, PROTOCOL_VERSION};
+
+/// How to launch one external MCP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpServerConfig {
+    /// Display name (used in logs and error messages).
+    pub name: String,
+    /// Executable to start.
+    pub command: String,
+    /// Command-line arguments.
+    pub args: Vec<String>,
+    /// Extra environment variables for the child process.
+    pub env: Vec<(String, String)>,
+    /// How long to wait for each response. A server that misses it is killed.
+    pub timeout: Duration,
+}
+
+/// Default per-request timeout for MCP servers.
+pub const DEFAULT_MCP_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl McpServerConfig {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self { name: name.into(), command: command.into(), args: Vec::new(), env: Vec::new(), timeout: DEFAULT_MCP_TIMEOUT }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Parse a whitespace separated spec such as `"python3 server.py --flag"`.
+    /// An optional `name=` prefix sets the display name (`"files=npx some-mcp-server"`);
+    /// otherwise the executable's file stem is used.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, rest) = match spec.split_once('=') {
+            Some((n, r)) if !n.trim().is_empty() && !n.contains(char::is_whitespace) => (Some(n.trim()), r),
+            _ => (None, spec),
+        };
+        let mut parts = rest.split_whitespace();
+        let command = parts.next().ok_or_else(|| eyre!("empty MCP server command: '{spec}'"))?;
+        let name = name.map(str::to_string).unwrap_or_else(|| {
+            std::path::Path::new(command)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(command)
+                .to_string()
+        });
+        Ok(Self::new(name, command).args(parts))
+    }
+}
+
+/// Tool metadata returned by `tools/list`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+/// stdin of the server plus the lines its stdout produced. A reader thread feeds `lines`,
+/// so a response can be awaited with a deadline instead of blocking in `read_line`.
+struct Transport {
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+/// Connection to one running MCP server process. The child is killed on drop
+/// or when a request times out.
+pub struct McpClient {
+    name: String,
+    timeout: Duration,
+    child: Mutex<Child>,
+    transport: Mutex<Transport>,
+    next_id: AtomicU64,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient").field("name", &self.name).finish()
+    }
+}
+
+impl McpClient {
+    /// Spawn the server process and perform the `initialize` handshake.
+    pub fn spawn(config: &McpServerConfig) -> Result<Arc<Self>> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(config.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // stderr would corrupt the TUI; MCP servers log there by convention
+            .stderr(Stdio::null())
+            .spawn()
+            .wrap_err_with(|| format!("spawning MCP server '{}' ({})", config.name, config.command))?;
+        let stdin = child.stdin.take().ok_or_else(|| eyre!("MCP server stdin unavailable"))?;
+        let stdout = child.stdout.take().ok_or_else(|| eyre!("MCP server stdout unavailable"))?;
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Ends when the server exits (EOF) or the client is gone
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() { break; }
+            }
+        });
+
+        let client = Arc::new(Self {
+            name: config.name.clone(),
+            timeout: config.timeout,
+            child: Mutex::new(child),
+            transport: Mutex::new(Transport { stdin, lines }),
+            next_id: AtomicU64::new(1),
+        });
+        client.initialize()?;
+        info!(target: "mcp", server = %client.name, "mcp_client_connected");
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+
+    fn initialize(&self) -> Result<()> {
+        let result = self.request("initialize", Some(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") }
+        })))?;
+        debug!(target: "mcp", server = %self.name, result = %result, "mcp_initialize_result");
+        self.notify("notifications/initialized", None)
+    }
+
+    /// Send a request and block until the response with the same id arrives.
+    /// If none arrives within the configured timeout the server is killed and an error returned.
+    pub fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let req = JsonRpcRequest::new(id, method, params);
+        let mut transport = self.transport.lock().map_err(|_| eyre!("MCP transport poisoned"))?;
+        writeln!(transport.stdin, "{}", serde_json::to_string(&req)?)?;
+        transport.stdin.flush()?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let line = match transport.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(eyre!("MCP server '{}' closed the connection", self.name));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!(target: "mcp", server = %self.name, method, "mcp_request_timeout");
+                    self.kill();
+                    return Err(eyre!("MCP server '{}' did not answer {method} within {:?}", self.name, self.timeout));
+                }
+            };
+            let line = line.trim();
+            if line.is_empty() { continue; }
+            let msg: Value = match serde_json::from_str(line) {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!(target: "mcp", server = %self.name, error = %e, "mcp_non_json_line");
+                    continue;
+                }
+            };
+            // Skip server-initiated notifications and stale responses.
+            if msg.get("id") != Some(&json!(id)) { continue; }
+            let resp: JsonRpcResponse = serde_json::from_value(msg)?;
+            if let Some(err) = resp.error {
+                return Err(eyre!("MCP '{}' {method} failed ({}): {}", self.name, err.code, err.message));
+            }
+            return Ok(resp.result.unwrap_or(Value::Null));
+        }
+    }
+
+    /// Send a notification (no response expected).
+    pub fn notify(&self, method: &str, params: Option<Value>) -> Result<()> {
+        let req = JsonRpcRequest::notification(method, params);
+        let mut transport = self.transport.lock().map_err(|_| eyre!("MCP transport poisoned"))?;
+        writeln!(transport.stdin, "{}", serde_json::to_string(&req)?)?;
+        transport.stdin.flush()?;
+        Ok(())
+    }
+
+    /// `tools/list`
+    pub fn list_tools(&self) -> Result<Vec<RemoteTool>> {
+        let result = self.request("tools/list", None)?;
+        let tools = result
+            .get("tools")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| eyre!("tools/list result has no 'tools' array"))?;
+        Ok(tools
+            .iter()
+            .filter_map(|t| {
+                Some(RemoteTool {
+                    name: t.get("name")?.as_str()?.to_string(),
+                    description: t.get("description").and_then(|d| d.as_str()).unwrap_or("").to_string(),
+                    input_schema: t.get("inputSchema").cloned().unwrap_or_else(|| json!({"type": "object"})),
+                })
+            })
+            .collect())
+    }
+
+    /// `tools/call`. Text content that parses as JSON is returned as that JSON value,
+    /// other text is wrapped as `{ "text": ... }`. `isError: true` becomes an `Err`.
+    pub fn call_tool(&self, name: &str, arguments: &Value) -> Result<Value> {
+        let result = self.request("tools/call", Some(json!({ "name": name, "arguments": arguments })))?;
+        let text: Vec<&str> = result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|i| i.get("type").and_then(|t| t.as_str()) == Some("text"))
+                    .filter_map(|i| i.get("text").and_then(|t| t.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let text = text.join("\n");
+        if result.get("isError").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(eyre!("MCP tool '{name}' returned an error: {text}"));
+        }
+        Ok(serde_json::from_str(&text).unwrap_or_else(|_| json!({ "text": text })))
+    }
+}
+
+impl McpClient {
+    fn kill(&self) {
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Longest function name the OpenAI API accepts.
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Name a remote tool is exposed under: `<server>__<tool>`, with characters the OpenAI
+/// function-name pattern (`[a-zA-Z0-9_-]`) rejects replaced by `_`, cut to `MAX_TOOL_NAME_LEN`.
+pub fn mcp_tool_name(server: &str, tool: &str) -> String {
+    let clean = |s: &str| s.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect::<String>();
+    let mut name = format!("{}__{}", clean(server), clean(tool));
+    // Only ASCII is left, so any byte index is a char boundary
+    name.truncate(MAX_TOOL_NAME_LEN);
+    name
+}
+
+/// `mcp_tool_name` for every `(server, tool)` pair, with a hash of the original names appended
+/// to the ones that would otherwise collide (after cleaning or cutting), so each stays unique.
+pub fn unique_mcp_tool_names<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<String> {
+    let pairs: Vec<_> = pairs.into_iter().collect();
+    let names: Vec<_> = pairs.iter().map(|(server, tool)| mcp_tool_name(server, tool)).collect();
+    let mut counts = HashMap::new();
+    for name in &names {
+        *counts.entry(name.as_str()).or_insert(0) += 1;
+    }
+    names
+        .iter()
+        .zip(&pairs)
+        .map(|(name, (server, tool))| {
+            if counts[name.as_str()] == 1 {
+                return name.clone();
+            }
+            let suffix = format!("_{:016x}", fnv1a(format!("{server}\0{tool}").as_bytes()));
+            format!("{}{suffix}", &name[..name.len().min(MAX_TOOL_NAME_LEN - suffix.len())])
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Wrap remote tools as `ToolDefinition`s forwarding to their client, named by `unique_mcp_tool_names`.
+fn wrap_remote_tools(remotes: Vec<(Arc<McpClient>, RemoteTool)>) -> Vec<ToolDefinition> {
+    let names = unique_mcp_tool_names(remotes.iter().map(|(client, remote)| (client.name.as_str(), remote.name.as_str())));
+    remotes
+        .into_iter()
+        .zip(names)
+        .map(|((client, remote), name)| {
+            let remote_name = remote.name;
+            ToolDefinition::new(
+                name,
+                remote.description,
+                remote.input_schema,
+                Arc::new(move |args: &Value| client.call_tool(&remote_name, args)),
+            )
+        })
+        .collect()
+}
+
+/// The server's advertised tools, each paired with the client that serves it.
+fn list_remote_tools(client: &Arc<McpClient>) -> Result<Vec<(Arc<McpClient>, RemoteTool)>> {
+    Ok(client.list_tools()?.into_iter().map(|remote| (Arc::clone(client), remote)).collect())
+}
+
+/// Wrap every tool the server advertises as a `ToolDefinition` forwarding to `client`,
+/// named `<server>__<tool>` (see `mcp_tool_name`).
+pub fn build_mcp_tools(client: &Arc<McpClient>) -> Result<Vec<ToolDefinition>> {
+    Ok(wrap_remote_tools(list_remote_tools(client)?))
+}
+
+/// Start every configured server and collect their tools.
+/// Servers that fail to start are logged and skipped so one broken entry doesn't disable the rest.
+/// Names are made unique across all servers, not just within one.
+pub fn start_mcp_tools(configs: &[McpServerConfig]) -> Vec<ToolDefinition> {
+    let mut remotes = Vec::new();
+    for config in configs {
+        match McpClient::spawn(config).and_then(|c| list_remote_tools(&c)) {
+            Ok(mut t) => {
+                info!(target: "mcp", server = %config.name, tools = t.len(), "mcp_tools_loaded");
+                remotes.append(&mut t);
+            }
+            Err(e) => warn!(target: "mcp", server = %config.name, error = %e, "mcp_server_start_failed"),
+        }
+    }
+    wrap_remote_tools(remotes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_spec() {
+        let c = McpServerConfig::parse("python3 server.py --flag").unwrap();
+        assert_eq!(c.name, "python3");
+        assert_eq!(c.command, "python3");
+        assert_eq!(c.args, vec!["server.py", "--flag"]);
+
+        let named = McpServerConfig::parse("files=npx -y files-mcp").unwrap();
+        assert_eq!(named.name, "files");
+        assert_eq!(named.command, "npx");
+
+        assert!(McpServerConfig::parse("   ").is_err());
+    }
+
+    #[test]
+    fn tool_names_are_namespaced_by_server() {
+        assert_eq!(mcp_tool_name("files", "read"), "files__read");
+        assert_eq!(mcp_tool_name("my.server", "get state"), "my_server__get_state");
+    }
+
+    #[test]
+    fn long_names_are_cut_and_clashes_get_a_hash_suffix() {
+        let long = "x".repeat(80);
+        assert_eq!(mcp_tool_name("files", &long).len(), MAX_TOOL_NAME_LEN);
+
+        let a = format!("{long}_a");
+        let b = format!("{long}_b");
+        let names = unique_mcp_tool_names([("files", a.as_str()), ("files", b.as_str()), ("files", "read")]);
+        assert_ne!(names[0], names[1]);
+        assert!(names.iter().all(|n| n.len() <= MAX_TOOL_NAME_LEN), "{names:?}");
+        assert!(names[0].starts_with("files__xxx"));
+        assert_eq!(names[2], "files__read");
+        // Same input, same names
+        assert_eq!(names, unique_mcp_tool_names([("files", a.as_str()), ("files", b.as_str()), ("files", "read")]));
+
+        // Names that only clash after cleaning are told apart too
+        let cleaned = unique_mcp_tool_names([("s", "get.state"), ("s", "get state")]);
+        assert_ne!(cleaned[0], cleaned[1]);
+        assert!(cleaned[0].starts_with("s__get_state_"));
+    }
+}
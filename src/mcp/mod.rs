@@ -0,0 +1,15 @@
+//! Model Context Protocol (MCP) support over stdio JSON-RPC.
+//!
+//! - `server`: serve our `ToolDefinition`s to other agents / editors
+//! - `client`: start external MCP servers and wrap their tools as `ToolDefinition`s
+
+pub mod protocol; // JSON-RPC 2.0 message types
+pub mod server; // stdio MCP server
+pub mod client; // stdio MCP client (child process)
+
+pub use protocol::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, PROTOCOL_VERSION};
+pub use server::McpServer;
+pub use client::{
+    build_mcp_tools, mcp_tool_name, start_mcp_tools, unique_mcp_tool_names, McpClient, McpServerConfig, RemoteTool,
+    DEFAULT_MCP_TIMEOUT, MAX_TOOL_NAME_LEN,
+};
@@ -0,0 +1,75 @@
+//! JSON-RPC 2.0 message types used by the MCP stdio transport.
+//!
+//! Messages are exchanged as one JSON object per line (newline-delimited JSON).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// MCP protocol revision this implementation speaks.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error codes (subset).
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Incoming request or notification. Notifications have no `id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// Build a request with an id.
+    pub fn new(id: impl Into<Value>, method: &str, params: Option<Value>) -> Self {
+        Self { jsonrpc: "2.0".into(), id: Some(id.into()), method: method.into(), params }
+    }
+
+    /// Build a notification (no id, no response expected).
+    pub fn notification(method: &str, params: Option<Value>) -> Self {
+        Self { jsonrpc: "2.0".into(), id: None, method: method.into(), params }
+    }
+
+    pub fn is_notification(&self) -> bool { self.id.is_none() }
+}
+
+/// Error object carried in a failed response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// Response to a request. Exactly one of `result` / `error` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0".into(), id, result: Some(result), error: None }
+    }
+
+    pub fn failure(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            id,
+            result: None,
+            error: Some(JsonRpcError { code, message: message.into(), data: None }),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! MCP server: expose a set of `ToolDefinition`s over stdio JSON-RPC.
+//!
+//! Supported methods:
+//! - `initialize` / `notifications/initialized` / `ping`
+//! - `tools/list` built from `ToolDefinition::function_object()`
+//! - `tools/call` dispatched to `ToolDefinition::execute`
+
+use std::io::{BufRead, Write};
+
+use color_eyre::Result;
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::openai::tools::ToolDefinition;
+use super::protocol::{
+    JsonRpcRequest, JsonRpcResponse, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND,
+    PARSE_ERROR, PROTOCOL_VERSION,
+};
+
+/// Stdio MCP server holding the tools it serves.
+pub struct McpServer {
+    name: String,
+    version: String,
+    tools: Vec<ToolDefinition>,
+}
+
+impl McpServer {
+    /// Create a server for the given tools. Server name/version default to this crate's.
+    pub fn new(tools: Vec<ToolDefinition>) -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            tools,
+        }
+    }
+
+    /// Override the advertised server name.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn tools(&self) -> &[ToolDefinition] { &self.tools }
+
+    /// Handle one raw JSON line. Returns the serialized response, or `None` for notifications.
+    pub fn handle_line(&self, line: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(line) {
+            Err(e) => Some(JsonRpcResponse::failure(Value::Null, PARSE_ERROR, format!("parse error: {e}"))),
+            Ok(raw) => match serde_json::from_value::<JsonRpcRequest>(raw.clone()) {
+                Ok(req) => self.handle_request(req),
+                Err(e) => {
+                    let id = raw.get("id").cloned().unwrap_or(Value::Null);
+                    Some(JsonRpcResponse::failure(id, INVALID_REQUEST, format!("invalid request: {e}")))
+                }
+            },
+        };
+        response.map(|r| serde_json::to_string(&r).expect("serializable response"))
+    }
+
+    /// Handle a parsed request. Returns `None` for notifications.
+    pub fn handle_request(&self, req: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        debug!(target: "mcp", method = %req.method, "mcp_request");
+        let Some(id) = req.id.clone() else {
+            // Notifications (e.g. notifications/initialized) need no reply.
+            return None;
+        };
+        let params = req.params.unwrap_or(Value::Null);
+        let response = match req.method.as_str() {
+            "initialize" => JsonRpcResponse::success(id, self.initialize_result()),
+            "ping" => JsonRpcResponse::success(id, json!({})),
+            "tools/list" => JsonRpcResponse::success(id, self.list_tools()),
+            "tools/call" => match self.call_tool(&params) {
+                Ok(result) => JsonRpcResponse::success(id, result),
+                Err(message) => JsonRpcResponse::failure(id, INVALID_PARAMS, message),
+            },
+            other => JsonRpcResponse::failure(id, METHOD_NOT_FOUND, format!("method not found: {other}")),
+        };
+        Some(response)
+    }
+
+    fn initialize_result(&self) -> Value {
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": self.name, "version": self.version }
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|t| {
+                let f = t.function_object();
+                json!({
+                    "name": f.name,
+                    "description": f.description,
+                    "inputSchema": f.parameters.unwrap_or_else(|| json!({"type": "object"})),
+                })
+            })
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    /// Execute `tools/call`. Tool failures are reported as `isError: true` results (per MCP),
+    /// while malformed params / unknown tools become JSON-RPC errors.
+    fn call_tool(&self, params: &Value) -> std::result::Result<Value, String> {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "missing tool name".to_string())?;
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| format!("unknown tool: {name}"))?;
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        info!(target: "mcp", tool = name, "mcp_tools_call");
+        let (text, is_error) = match tool.execute(&args) {
+            Ok(v) => (v.to_string(), false),
+            Err(e) => (e.to_string(), true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error
+        }))
+    }
+
+    /// Serve requests from `reader` until EOF, writing one response line per request.
+    pub fn serve<R: BufRead, W: Write>(&self, reader: R, mut writer: W) -> Result<()> {
+        info!(target: "mcp", tools = self.tools.len(), "mcp_server_start");
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() { continue; }
+            if let Some(out) = self.handle_line(&line) {
+                writeln!(writer, "{out}")?;
+                writer.flush()?;
+            }
+        }
+        warn!(target: "mcp", "mcp_server_stdin_closed");
+        Ok(())
+    }
+
+    /// Serve over the process stdin/stdout.
+    pub fn serve_stdio(&self) -> Result<()> {
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        self.serve(stdin.lock(), stdout.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai::tools::{build_add_tool, build_get_constants_tool};
+
+    fn server() -> McpServer {
+        McpServer::new(vec![build_get_constants_tool(1, 2), build_add_tool()])
+    }
+
+    fn call(server: &McpServer, req: Value) -> Value {
+        let out = server.handle_line(&req.to_string()).expect("response");
+        serde_json::from_str(&out).unwrap()
+    }
+
+    #[test]
+    fn initialize_and_list() {
+        let s = server();
+        let init = call(&s, json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}));
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(s.handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+
+        let list = call(&s, json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}));
+        let tools = list["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[1]["name"], "add");
+        assert!(tools[1]["inputSchema"]["properties"].get("x").is_some());
+    }
+
+    #[test]
+    fn call_tool_and_errors() {
+        let s = server();
+        let ok = call(&s, json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+            "params": {"name": "add", "arguments": {"x": 2, "y": 5}}}));
+        assert_eq!(ok["result"]["isError"], false);
+        let text = ok["result"]["content"][0]["text"].as_str().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(text).unwrap()["sum"], 7);
+
+        let failed = call(&s, json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+            "params": {"name": "add", "arguments": {"x": 2}}}));
+        assert_eq!(failed["result"]["isError"], true);
+
+        let unknown = call(&s, json!({"jsonrpc": "2.0", "id": 5, "method": "tools/call", "params": {"name": "nope"}}));
+        assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+
+        let missing = call(&s, json!({"jsonrpc": "2.0", "id": 6, "method": "resources/list"}));
+        assert_eq!(missing["error"]["code"], METHOD_NOT_FOUND);
+
+        let garbage: Value = serde_json::from_str(&s.handle_line("{not json").unwrap()).unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn serve_reads_until_eof() {
+        let s = server();
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#, "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#, "\n",
+        );
+        let mut out = Vec::new();
+        s.serve(input.as_bytes(), &mut out).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+        assert_eq!(lines.len(), 2);
+    }
+}
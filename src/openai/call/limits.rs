@@ -0,0 +1,201 @@
+//! Tool result size policy applied in the multi-step loop before results enter the history.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use async_openai::types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs,
+};
+use async_openai::Client;
+use color_eyre::Result;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::config::Config;
+
+/// Default cap for a single tool result (bytes of the JSON text put into history).
+pub const DEFAULT_TOOL_RESULT_MAX_BYTES: usize = 16 * 1024;
+
+/// What to do when a tool result exceeds its cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizeStrategy {
+    /// Cut at the limit and append a marker.
+    #[default]
+    Truncate,
+    /// Ask the model for a summary that fits; falls back to `Truncate` on failure.
+    Summarize,
+}
+
+impl Display for OversizeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OversizeStrategy::Truncate => write!(f, "truncate"),
+            OversizeStrategy::Summarize => write!(f, "summarize"),
+        }
+    }
+}
+
+/// Per-tool maximum result size with a shared default.
+#[derive(Debug, Clone)]
+pub struct ToolResultLimits {
+    pub default_max_bytes: usize,
+    pub per_tool: HashMap<String, usize>,
+    pub strategy: OversizeStrategy,
+}
+
+impl Default for ToolResultLimits {
+    fn default() -> Self {
+        Self {
+            default_max_bytes: DEFAULT_TOOL_RESULT_MAX_BYTES,
+            per_tool: HashMap::new(),
+            strategy: OversizeStrategy::Truncate,
+        }
+    }
+}
+
+impl ToolResultLimits {
+    /// Override the cap for one tool.
+    pub fn with_tool_limit(mut self, name: impl Into<String>, max_bytes: usize) -> Self {
+        self.per_tool.insert(name.into(), max_bytes);
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: OversizeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Cap that applies to `tool_name`.
+    pub fn max_bytes_for(&self, tool_name: &str) -> usize {
+        self.per_tool.get(tool_name).copied().unwrap_or(self.default_max_bytes)
+    }
+}
+
+/// Outcome of applying the limit to one result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitedResult {
+    pub text: String,
+    pub original_bytes: usize,
+    /// `None` when the result fit and was left untouched.
+    pub applied: Option<OversizeStrategy>,
+}
+
+/// Longest prefix of `s` that is at most `max_bytes` long and ends on a char boundary.
+pub fn truncate_str(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Truncate `text` to `max_bytes` (marker included) and append a marker describing the cut.
+pub fn truncate_with_marker(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let marker = format!("\n...[truncated: {} bytes total]", text.len());
+    let keep = max_bytes.saturating_sub(marker.len());
+    format!("{}{marker}", truncate_str(text, keep))
+}
+
+/// Apply the limit using truncation only (no network).
+pub fn limit_tool_result(limits: &ToolResultLimits, tool_name: &str, text: &str) -> LimitedResult {
+    let max = limits.max_bytes_for(tool_name);
+    if text.len() <= max {
+        return LimitedResult { text: text.to_string(), original_bytes: text.len(), applied: None };
+    }
+    LimitedResult {
+        text: truncate_with_marker(text, max),
+        original_bytes: text.len(),
+        applied: Some(OversizeStrategy::Truncate),
+    }
+}
+
+/// Apply the configured strategy. `Summarize` calls the model and falls back to truncation on error.
+pub async fn enforce_tool_result_limit(
+    limits: &ToolResultLimits,
+    tool_name: &str,
+    text: &str,
+    config: &Config,
+) -> LimitedResult {
+    let max = limits.max_bytes_for(tool_name);
+    if text.len() <= max || limits.strategy == OversizeStrategy::Truncate {
+        return limit_tool_result(limits, tool_name, text);
+    }
+    match summarize_tool_result(tool_name, text, max, config).await {
+        Ok(summary) => LimitedResult {
+            text: truncate_with_marker(&format!("[summarized] {summary}"), max),
+            original_bytes: text.len(),
+            applied: Some(OversizeStrategy::Summarize),
+        },
+        Err(e) => {
+            warn!(target: "openai", tool = tool_name, error = %e, "tool_result_summarize_failed");
+            limit_tool_result(limits, tool_name, text)
+        }
+    }
+}
+
+/// Ask the model for a compact summary of an oversized tool result.
+async fn summarize_tool_result(tool_name: &str, text: &str, max_bytes: usize, config: &Config) -> Result<String> {
+    let client = Client::new();
+    // Send at most a few times the cap so the summarization request itself stays bounded.
+    let input = truncate_str(text, max_bytes.saturating_mul(4));
+    let system = ChatCompletionRequestSystemMessageArgs::default()
+        .content("You summarize tool outputs. Keep every fact, number, name and identifier needed to answer the user; drop boilerplate.")
+        .build()?;
+    let user = ChatCompletionRequestUserMessageArgs::default()
+        .content(format!("Tool `{tool_name}` returned the following output. Summarize it in under {max_bytes} bytes.\n\n{input}"))
+        .build()?;
+    let req = CreateChatCompletionRequestArgs::default()
+        .model(&config.model)
+        .messages([system.into(), user.into()])
+        .max_tokens(config.max_tokens)
+        .build()?;
+    let resp = client.chat().create(req).await?;
+    debug!(target: "openai", tool = tool_name, "tool_result_summarized");
+    Ok(resp
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_respects_char_boundary() {
+        let s = "あいう"; // 3 bytes each
+        assert_eq!(truncate_str(s, 4), "あ");
+        assert_eq!(truncate_str(s, 9), "あいう");
+        assert_eq!(truncate_str(s, 0), "");
+    }
+
+    #[test]
+    fn small_result_untouched() {
+        let limits = ToolResultLimits::default();
+        let r = limit_tool_result(&limits, "x", "{\"ok\":true}");
+        assert_eq!(r.applied, None);
+        assert_eq!(r.text, "{\"ok\":true}");
+    }
+
+    #[test]
+    fn per_tool_limit_truncates_with_marker() {
+        let limits = ToolResultLimits::default().with_tool_limit("tavily_search", 64);
+        let big = "x".repeat(500);
+        let r = limit_tool_result(&limits, "tavily_search", &big);
+        assert_eq!(r.applied, Some(OversizeStrategy::Truncate));
+        assert_eq!(r.original_bytes, 500);
+        assert!(r.text.len() <= 64);
+        assert!(r.text.ends_with("[truncated: 500 bytes total]"));
+        // other tools still use the default cap
+        assert_eq!(limit_tool_result(&limits, "add", &big).applied, None);
+    }
+}
@@ -1,10 +1,13 @@
+use async_openai::types::ChatCompletionRequestMessage;
 use crate::config::Config;
 use crate::openai::tools::ToolDefinition;
-use crate::openai::ConversationHistory;
+use crate::openai::{estimate_request_overhead, ConversationHistory, MessageRole};
 use color_eyre::Result;
+use futures::future::BoxFuture;
 use tokio::runtime::Runtime;
 use tracing::{debug, info, instrument};
 
+use super::limits::enforce_tool_result_limit;
 use super::proposer::propose_tool_call;
 use super::resolver::resolve_and_execute_tool_call;
 use super::types::{MultiStepAnswer, MultiStepLogEvent, ToolCallDecision, ToolResolution};
@@ -17,11 +20,12 @@ pub async fn multi_step_tool_answer(
     max_loops: Option<usize>,
 ) -> Result<MultiStepAnswer> {
     multi_step_tool_answer_with_logger_internal(
-        original_user_prompt,
+        &mut history_from_prompt(original_user_prompt),
         tools,
         config,
         max_loops,
         None,
+        model_proposer(tools, config),
     ).await
 }
 
@@ -31,40 +35,105 @@ pub async fn multi_step_tool_answer_with_logger(
     tools: &[ToolDefinition],
     config: &Config,
     max_loops: Option<usize>,
-    logger: impl FnMut(&MultiStepLogEvent),
+    logger: impl FnMut(&MultiStepLogEvent) + Send,
+) -> Result<MultiStepAnswer> {
+    multi_step_tool_answer_with_history_and_logger(
+        &mut history_from_prompt(original_user_prompt),
+        tools,
+        config,
+        max_loops,
+        logger,
+    ).await
+}
+
+/// Like [`multi_step_tool_answer_with_logger`], but runs on a caller-owned history so a session can keep
+/// its conversation across prompts. The caller pushes the new user message first; the tool calls and
+/// results of this run are left in `history` (the final answer is not added).
+#[instrument(name = "multi_step_tool_answer_with_history_and_logger", skip(history, tools, config, logger), fields(history_len = history.len()))]
+pub async fn multi_step_tool_answer_with_history_and_logger(
+    history: &mut ConversationHistory,
+    tools: &[ToolDefinition],
+    config: &Config,
+    max_loops: Option<usize>,
+    logger: impl FnMut(&MultiStepLogEvent) + Send,
 ) -> Result<MultiStepAnswer> {
     let mut user_logger = logger;
     let mut log_and_forward = |ev: &MultiStepLogEvent| {
         debug!(target: "openai", event = %ev, "multi_step_event");
         user_logger(ev);
     };
-    let mut opt_logger: Option<&mut dyn FnMut(&MultiStepLogEvent)> = Some(&mut log_and_forward);
+    let opt_logger: Option<&mut Logger> = Some(&mut log_and_forward);
     multi_step_tool_answer_with_logger_internal(
-        original_user_prompt,
+        history,
         tools,
         config,
         max_loops,
-        opt_logger.as_deref_mut(),
+        opt_logger,
+        model_proposer(tools, config),
     ).await
 }
 
-async fn multi_step_tool_answer_with_logger_internal(
-    original_user_prompt: &str,
+/// Continue the multi-step loop from an existing history (e.g. one loaded with
+/// `ConversationHistory::from_json` from a bug report) instead of a single prompt.
+#[instrument(name = "multi_step_tool_answer_with_history", skip(history, tools, config), fields(history_len = history.len()))]
+pub async fn multi_step_tool_answer_with_history(
+    mut history: ConversationHistory,
+    tools: &[ToolDefinition],
+    config: &Config,
+    max_loops: Option<usize>,
+) -> Result<MultiStepAnswer> {
+    multi_step_tool_answer_with_logger_internal(&mut history, tools, config, max_loops, None, model_proposer(tools, config)).await
+}
+
+fn history_from_prompt(prompt: &str) -> ConversationHistory {
+    let mut history = ConversationHistory::new();
+    history.add_user(prompt);
+    history
+}
+
+/// Event callback; `Send` so the loop can run on a spawned task (the TUI worker runs sessions in parallel).
+type Logger<'a> = dyn FnMut(&MultiStepLogEvent) + Send + 'a;
+
+/// Asks the model for the next step via `propose_tool_call`.
+fn model_proposer<'a>(
+    tools: &'a [ToolDefinition],
+    config: &'a Config,
+) -> impl FnMut(Vec<ChatCompletionRequestMessage>) -> BoxFuture<'a, Result<ToolCallDecision>> + Send + 'a {
+    move |history| Box::pin(async move { propose_tool_call(&history, "", tools, config).await })
+}
+
+/// The loop itself. `propose` gets the current history and returns the next step
+/// (`model_proposer` in production, a script in tests).
+async fn multi_step_tool_answer_with_logger_internal<'p>(
+    history: &mut ConversationHistory,
     tools: &[ToolDefinition],
     config: &Config,
     max_loops: Option<usize>,
-    mut logger: Option<&mut dyn FnMut(&MultiStepLogEvent)>,
+    mut logger: Option<&mut Logger<'_>>,
+    mut propose: impl FnMut(Vec<ChatCompletionRequestMessage>) -> BoxFuture<'p, Result<ToolCallDecision>> + Send,
 ) -> Result<MultiStepAnswer> {
     let max_loops = max_loops.unwrap_or(5);
+    // The system prompt and tool schemas go with every request, so the history gets what is left
+    let overhead = estimate_request_overhead(&config.system_prompt, tools, config.context_budget.tokenizer());
+    let budget = config.context_budget.reserving(overhead);
     let mut steps: Vec<ToolResolution> = Vec::new();
     let mut truncated = false;
-    let mut history = ConversationHistory::new();
-    history.add_user(original_user_prompt);
+    // Used in the early-failure answer: the latest user message (the prompt of this run).
+    let original_user_prompt = history
+        .last_index_of(MessageRole::User)
+        .and_then(|i| history.text(i))
+        .unwrap_or_default();
 
     for iteration in 1..=max_loops {
         debug!(target: "openai", iteration, "multi_step_iteration_start");
         if let Some(cb) = logger.as_deref_mut() { cb(&MultiStepLogEvent::IterationStart { iteration }); }
-        let decision = propose_tool_call(history.as_slice(), "", tools, config).await?;
+        let trim = history.fit_to_budget(&budget);
+        if trim.trimmed()
+            && let Some(cb) = logger.as_deref_mut()
+        {
+            cb(&MultiStepLogEvent::ContextTrimmed { iteration, removed: trim.removed, excerpted: trim.excerpted, tokens_before: trim.tokens_before, tokens_after: trim.tokens_after });
+        }
+        let decision = propose(history.as_slice().to_vec()).await?;
         if let Some(cb) = logger.as_deref_mut() { cb(&MultiStepLogEvent::Proposed { iteration, decision: decision.clone() }); }
         match decision {
             ToolCallDecision::Text(text) => {
@@ -108,15 +177,23 @@ async fn multi_step_tool_answer_with_logger_internal(
                 }
 
                 if let ToolResolution::Executed { name, result } = &resolution {
-                    history.add_function(name, result.to_string());
-                    if let Some(cb) = logger.as_deref_mut() { cb(&MultiStepLogEvent::HistoryFunctionAppended { iteration, name: name.clone(), result: result.clone() }); }
+                    // Enforce the shared size policy before the result consumes context window.
+                    let limited = enforce_tool_result_limit(&config.tool_result_limits, name, &result.to_string(), config).await;
+                    if let Some(strategy) = limited.applied
+                        && let Some(cb) = logger.as_deref_mut()
+                    {
+                        cb(&MultiStepLogEvent::ToolResultLimited { iteration, name: name.clone(), original_bytes: limited.original_bytes, limited_bytes: limited.text.len(), strategy });
+                    }
+                    history.add_function(name, &limited.text);
+                    // Report exactly what was stored, i.e. the limited text
+                    if let Some(cb) = logger.as_deref_mut() { cb(&MultiStepLogEvent::HistoryFunctionAppended { iteration, name: name.clone(), content: limited.text }); }
                 }
             }
         }
     }
 
     truncated = true;
-    if let Some(cb) = logger.as_deref_mut() { cb(&MultiStepLogEvent::Truncated { max_loops }); }
+    if let Some(cb) = logger { cb(&MultiStepLogEvent::Truncated { max_loops }); }
     let final_answer = format!(
         "最大ループ回数({})に達したため打ち切りました。これまでの function 結果(JSON)を参考に最終回答をまとめてください。",
         max_loops
@@ -141,7 +218,7 @@ pub fn multi_step_tool_answer_blocking_with_logger(
     tools: &[ToolDefinition],
     config: &Config,
     max_loops: Option<usize>,
-    logger: impl FnMut(&MultiStepLogEvent),
+    logger: impl FnMut(&MultiStepLogEvent) + Send,
 ) -> Result<MultiStepAnswer> {
     let max_loops_val = max_loops.unwrap_or(5);
     info!(target: "openai", model = %config.model, max_tokens = config.max_tokens, max_loops = max_loops_val, "multi_step_blocking_request");
@@ -152,17 +229,70 @@ pub fn multi_step_tool_answer_blocking_with_logger(
         user_logger(ev);
     };
 
-    let mut opt_logger: Option<&mut dyn FnMut(&MultiStepLogEvent)> = Some(&mut log_and_forward);
+    let opt_logger: Option<&mut Logger> = Some(&mut log_and_forward);
     let rt = Runtime::new()?;
     let result = rt.block_on(multi_step_tool_answer_with_logger_internal(
-        original_user_prompt,
+        &mut history_from_prompt(original_user_prompt),
         tools,
         config,
         max_loops,
-        opt_logger.as_deref_mut(),
+        opt_logger,
+        model_proposer(tools, config),
     ));
 
     let result = result?;
     info!(target: "openai", iterations = result.iterations, truncated = result.truncated, steps = result.steps.len(), final_len = result.final_answer.len(), "multi_step_blocking_done");
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai::call::ToolResultLimits;
+    use crate::openai::tools::ToolParametersBuilder;
+    use serde_json::json;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    /// Proposer that replays `decisions` in order
+    fn scripted(decisions: Vec<ToolCallDecision>) -> impl FnMut(Vec<ChatCompletionRequestMessage>) -> BoxFuture<'static, Result<ToolCallDecision>> + Send {
+        let mut decisions = VecDeque::from(decisions);
+        move |_| {
+            let next = decisions.pop_front().expect("scripted decision");
+            Box::pin(async move { Ok(next) })
+        }
+    }
+
+    #[tokio::test]
+    async fn history_and_event_hold_the_same_limited_result() {
+        let big = ToolDefinition::new(
+            "big",
+            "returns a large result",
+            ToolParametersBuilder::new_object().build(),
+            Arc::new(|_| Ok(json!({"data": "x".repeat(1_000)}))),
+        );
+        let mut config = Config::new();
+        config.tool_result_limits = ToolResultLimits::default().with_tool_limit("big", 200);
+        let mut history = history_from_prompt("go");
+        let mut appended = Vec::new();
+        let mut logger = |ev: &MultiStepLogEvent| {
+            if let MultiStepLogEvent::HistoryFunctionAppended { content, .. } = ev {
+                appended.push(content.clone());
+            }
+        };
+        let script = scripted(vec![
+            ToolCallDecision::ToolCall { name: "big".into(), arguments: "{}".into() },
+            ToolCallDecision::Text("done".into()),
+        ]);
+
+        let answer = multi_step_tool_answer_with_logger_internal(&mut history, &[big], &config, Some(3), Some(&mut logger), script)
+            .await
+            .unwrap();
+        assert_eq!(answer.final_answer, "done");
+
+        let stored = history.text(history.last_index_of(MessageRole::Function).unwrap()).unwrap();
+        assert!(stored.len() <= 200, "{} bytes", stored.len());
+        assert!(stored.contains("[truncated"));
+        assert_eq!(appended, [stored]);
+    }
+}
@@ -23,7 +23,7 @@ pub async fn propose_tool_call(
     let client = Client::new();
 
     let system = ChatCompletionRequestSystemMessageArgs::default()
-        .content("あなたは簡潔な日本語で答えるアシスタントです。")
+        .content(config.system_prompt.as_str())
         .build()?;
     let user = ChatCompletionRequestUserMessageArgs::default()
         .content(prompt)
@@ -53,12 +53,12 @@ pub async fn propose_tool_call(
         None => return Ok(ToolCallDecision::Text("(応答なし)".to_string())),
     };
 
-    if let Some(tool_calls) = &choice.message.tool_calls {
-        if let Some(first) = tool_calls.first() {
-            let name = first.function.name.clone();
-            let arguments = first.function.arguments.clone();
-            return Ok(ToolCallDecision::ToolCall { name, arguments });
-        }
+    if let Some(tool_calls) = &choice.message.tool_calls
+        && let Some(first) = tool_calls.first()
+    {
+        let name = first.function.name.clone();
+        let arguments = first.function.arguments.clone();
+        return Ok(ToolCallDecision::ToolCall { name, arguments });
     }
 
     let text = choice
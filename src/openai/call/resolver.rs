@@ -20,15 +20,15 @@ pub fn resolve_and_execute_tool_call(
                 Ok(v) => v,
                 Err(e) => {
                     return ToolResolution::ArgumentsParseError {
-                        name: tool.name.to_string(),
+                        name: tool.name.clone(),
                         raw: arguments,
                         error: e.to_string(),
                     };
                 }
             };
             match tool.execute(&parsed) {
-                Ok(v) => ToolResolution::Executed { name: tool.name.to_string(), result: v },
-                Err(e) => ToolResolution::ExecutionError { name: tool.name.to_string(), error: e.to_string() },
+                Ok(v) => ToolResolution::Executed { name: tool.name.clone(), result: v },
+                Err(e) => ToolResolution::ExecutionError { name: tool.name.clone(), error: e.to_string() },
             }
         }
     }
@@ -1,13 +1,18 @@
+use serde::Serialize;
 use serde_json::Value;
+
+use super::limits::OversizeStrategy;
 use std::fmt::{self, Display};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ToolCallDecision {
     Text(String),
     ToolCall { name: String, arguments: String },
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ToolResolution {
     ModelText(String),
     Executed { name: String, result: Value },
@@ -51,7 +56,7 @@ impl Display for ToolResolution {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MultiStepAnswer {
     pub final_answer: String,
     pub steps: Vec<ToolResolution>,
@@ -64,7 +69,9 @@ pub enum MultiStepLogEvent {
     IterationStart { iteration: usize },
     Proposed { iteration: usize, decision: ToolCallDecision },
     Resolved { iteration: usize, resolution: ToolResolution },
-    HistoryFunctionAppended { iteration: usize, name: String, result: Value },
+    HistoryFunctionAppended { iteration: usize, name: String, content: String },
+    ContextTrimmed { iteration: usize, removed: usize, excerpted: bool, tokens_before: usize, tokens_after: usize },
+    ToolResultLimited { iteration: usize, name: String, original_bytes: usize, limited_bytes: usize, strategy: OversizeStrategy },
     FinalText { iteration: usize, text: String },
     EarlyFailure { iteration: usize, resolution: ToolResolution },
     Truncated { max_loops: usize },
@@ -76,7 +83,9 @@ impl Display for MultiStepLogEvent {
             MultiStepLogEvent::IterationStart { iteration } => write!(f, "IterationStart #{}", iteration),
             MultiStepLogEvent::Proposed { iteration, decision } => write!(f, "Proposed @{} => {}", iteration, decision),
             MultiStepLogEvent::Resolved { iteration, resolution } => write!(f, "Resolved @{} => {}", iteration, resolution),
-            MultiStepLogEvent::HistoryFunctionAppended { iteration, name, result } => write!(f, "HistoryFunctionAppended @{} name={} result={}", iteration, name, result),
+            MultiStepLogEvent::HistoryFunctionAppended { iteration, name, content } => write!(f, "HistoryFunctionAppended @{} name={} content={}", iteration, name, content),
+            MultiStepLogEvent::ContextTrimmed { iteration, removed, excerpted, tokens_before, tokens_after } => write!(f, "ContextTrimmed @{} removed={} excerpted={} tokens {} -> {}", iteration, removed, excerpted, tokens_before, tokens_after),
+            MultiStepLogEvent::ToolResultLimited { iteration, name, original_bytes, limited_bytes, strategy } => write!(f, "ToolResultLimited @{} name={} {}: {} -> {} bytes", iteration, name, strategy, original_bytes, limited_bytes),
             MultiStepLogEvent::FinalText { iteration, text } => write!(f, "FinalText @{} len={}", iteration, text.len()),
             MultiStepLogEvent::EarlyFailure { iteration, resolution } => write!(f, "EarlyFailure @{} => {}", iteration, resolution),
             MultiStepLogEvent::Truncated { max_loops } => write!(f, "Truncated after {} loops", max_loops),
@@ -0,0 +1,367 @@
+//! Context-window management for `ConversationHistory`.
+//!
+//! Token counts are estimated through a pluggable [`Tokenizer`]; without one a character
+//! heuristic is used. [`ConversationHistory::fit_to_budget`] trims the history according to a
+//! [`ContextStrategy`] so long sessions keep fitting in the model's context window.
+//! The system prompt and tool schemas sent with every request are not part of the history;
+//! reserve them with [`ContextBudget::reserving`] (the multi-step loop does).
+//!
+//! None of the strategies calls the model: [`ContextStrategy::Excerpt`] keeps clipped lines of the
+//! dropped turns rather than a written summary.
+
+use std::sync::Arc;
+
+use async_openai::types::{ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs};
+
+use super::history::{message_role, message_text, ConversationHistory, MessageMeta};
+use super::tools::ToolDefinition;
+
+/// Prefix that marks a message generated by [`ContextStrategy::Excerpt`].
+pub const CONTEXT_EXCERPT_PREFIX: &str = "[Earlier conversation excerpts]";
+
+/// Per-message overhead (role, separators) added on top of the content estimate.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Each dropped message contributes at most this many chars to the excerpt message.
+const EXCERPT_LINE_MAX_CHARS: usize = 120;
+/// The excerpt message may use at most `max_tokens / EXCERPT_BUDGET_DIVISOR` tokens.
+const EXCERPT_BUDGET_DIVISOR: usize = 4;
+
+/// Counts tokens for a piece of text.
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Fallback estimate: ~4 ASCII chars per token, 1 token per non-ASCII char (CJK etc.).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharHeuristicTokenizer;
+
+impl Tokenizer for CharHeuristicTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        let (ascii, other) = text.chars().fold((0usize, 0usize), |(a, o), c| {
+            if c.is_ascii() { (a + 1, o) } else { (a, o + 1) }
+        });
+        ascii.div_ceil(4) + other
+    }
+}
+
+/// How to shrink the history when it exceeds the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContextStrategy {
+    /// Drop the oldest messages.
+    DropOldest,
+    /// Drop the oldest messages but always keep the first user message (the original task).
+    #[default]
+    PinFirstUser,
+    /// Keep the first user message and replace dropped turns with one message holding a clipped
+    /// excerpt of each (no model call).
+    Excerpt,
+}
+
+/// Token budget for the history sent to the model.
+#[derive(Clone)]
+pub struct ContextBudget {
+    pub max_tokens: usize,
+    pub strategy: ContextStrategy,
+    /// `None` uses [`CharHeuristicTokenizer`].
+    pub tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+impl std::fmt::Debug for ContextBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextBudget")
+            .field("max_tokens", &self.max_tokens)
+            .field("strategy", &self.strategy)
+            .field("tokenizer", &self.tokenizer.as_ref().map(|_| "custom").unwrap_or("char_heuristic"))
+            .finish()
+    }
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self { max_tokens: 16_000, strategy: ContextStrategy::default(), tokenizer: None }
+    }
+}
+
+impl ContextBudget {
+    pub fn new(max_tokens: usize, strategy: ContextStrategy) -> Self {
+        Self { max_tokens, strategy, tokenizer: None }
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    pub fn tokenizer(&self) -> &dyn Tokenizer {
+        match &self.tokenizer {
+            Some(t) => t.as_ref(),
+            None => &CharHeuristicTokenizer,
+        }
+    }
+
+    /// The same budget with `tokens` set aside for content sent outside the history.
+    pub fn reserving(&self, tokens: usize) -> Self {
+        Self { max_tokens: self.max_tokens.saturating_sub(tokens), ..self.clone() }
+    }
+}
+
+/// Estimated tokens a request spends besides the history: the system prompt and the tool schemas.
+pub fn estimate_request_overhead(system_prompt: &str, tools: &[ToolDefinition], tokenizer: &dyn Tokenizer) -> usize {
+    let schemas: usize = tools
+        .iter()
+        .map(|t| tokenizer.count_tokens(&serde_json::to_string(&t.as_chat_tool()).unwrap_or_default()))
+        .sum();
+    tokenizer.count_tokens(system_prompt) + MESSAGE_OVERHEAD_TOKENS + schemas
+}
+
+/// Result of [`ConversationHistory::fit_to_budget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrimReport {
+    pub removed: usize,
+    pub excerpted: bool,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+}
+
+impl TrimReport {
+    pub fn trimmed(&self) -> bool { self.removed > 0 }
+}
+
+/// Estimated tokens of a single message.
+pub fn estimate_message_tokens(msg: &ChatCompletionRequestMessage, tokenizer: &dyn Tokenizer) -> usize {
+    tokenizer.count_tokens(&message_text(msg)) + MESSAGE_OVERHEAD_TOKENS
+}
+
+fn estimate_all(messages: &[ChatCompletionRequestMessage], tokenizer: &dyn Tokenizer) -> usize {
+    messages.iter().map(|m| estimate_message_tokens(m, tokenizer)).sum()
+}
+
+/// Build the excerpt message for `dropped` (oldest first). Lines of an earlier excerpt message are
+/// carried over; the oldest lines are omitted once the message exceeds `max_tokens`.
+fn excerpt_message(
+    dropped: &[(ChatCompletionRequestMessage, MessageMeta)],
+    max_tokens: usize,
+    tokenizer: &dyn Tokenizer,
+) -> ChatCompletionRequestMessage {
+    let mut lines: Vec<String> = Vec::new();
+    let mut omitted = 0usize;
+    for (msg, _) in dropped {
+        let text = message_text(msg);
+        if let Some(body) = text.strip_prefix(CONTEXT_EXCERPT_PREFIX) {
+            for line in body.lines().filter(|l| !l.trim().is_empty()) {
+                match line.strip_prefix("(").and_then(|l| l.split_once(" earlier items omitted)")) {
+                    Some((n, _)) => omitted += n.parse::<usize>().unwrap_or(0),
+                    None => lines.push(line.to_string()),
+                }
+            }
+            continue;
+        }
+        let mut clipped: String = text.chars().take(EXCERPT_LINE_MAX_CHARS).collect();
+        if clipped.len() < text.len() { clipped.push('…'); }
+        lines.push(format!("- {}: {}", message_role(msg), clipped.replace('\n', " ")));
+    }
+    let render = |lines: &[String], omitted: usize| {
+        let mut out = vec![CONTEXT_EXCERPT_PREFIX.to_string()];
+        if omitted > 0 { out.push(format!("({omitted} earlier items omitted)")); }
+        out.extend_from_slice(lines);
+        out.join("\n")
+    };
+    // Estimate line by line (one extra token per newline), then confirm with the rendered text,
+    // which only re-renders when the per-line estimate was too optimistic.
+    let mut total = tokenizer.count_tokens(&render(&[], omitted)) + 1;
+    total += lines.iter().map(|l| tokenizer.count_tokens(l) + 1).sum::<usize>();
+    let mut start = 0;
+    while start < lines.len() && total > max_tokens {
+        total -= tokenizer.count_tokens(&lines[start]) + 1;
+        start += 1;
+    }
+    while start < lines.len() && tokenizer.count_tokens(&render(&lines[start..], omitted + start)) > max_tokens {
+        start += 1;
+    }
+    ChatCompletionRequestUserMessageArgs::default()
+        .content(render(&lines[start..], omitted + start))
+        .build()
+        .expect("valid user message")
+        .into()
+}
+
+impl ConversationHistory {
+    /// Estimated token count of the whole history.
+    pub fn estimate_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        estimate_all(self.as_slice(), tokenizer)
+    }
+
+    /// Shrink the history until it fits `budget.max_tokens`.
+    /// The most recent message is always kept, so a single oversized message can still exceed the budget.
+    pub fn fit_to_budget(&mut self, budget: &ContextBudget) -> TrimReport {
+        let tokenizer = budget.tokenizer();
+        let tokens_before = self.estimate_tokens(tokenizer);
+        let mut report = TrimReport { tokens_before, tokens_after: tokens_before, ..Default::default() };
+        if tokens_before <= budget.max_tokens || self.len() <= 1 {
+            return report;
+        }
+
+        let entries = self.take_entries();
+        let pinned = match budget.strategy {
+            ContextStrategy::DropOldest => None,
+            ContextStrategy::PinFirstUser | ContextStrategy::Excerpt => entries
+                .iter()
+                .position(|(m, _)| matches!(m, ChatCompletionRequestMessage::User(_))),
+        };
+        let (head, rest): (Vec<_>, Vec<_>) = match pinned {
+            Some(i) if i + 1 < entries.len() => {
+                let mut rest = entries;
+                let head = vec![rest.remove(i)];
+                (head, rest)
+            }
+            _ => (Vec::new(), entries),
+        };
+
+        // Drop the oldest `k` of `rest` (always keeping the newest) until the kept messages fit.
+        // Every message is counted once and subtracted as it is dropped; with `Excerpt`,
+        // room for the excerpt message (at most its token cap) is reserved once anything is dropped.
+        let excerpt = budget.strategy == ContextStrategy::Excerpt;
+        let excerpt_max = budget.max_tokens / EXCERPT_BUDGET_DIVISOR;
+        let rest_tokens: Vec<usize> = rest.iter().map(|(m, _)| estimate_message_tokens(m, tokenizer)).collect();
+        let head_tokens: usize = head.iter().map(|(m, _)| estimate_message_tokens(m, tokenizer)).sum();
+        let mut kept = head_tokens + rest_tokens.iter().sum::<usize>();
+        let max_drop = rest.len().saturating_sub(1);
+        let mut k = 0;
+        while k < max_drop && kept + if excerpt && k > 0 { excerpt_max + MESSAGE_OVERHEAD_TOKENS } else { 0 } > budget.max_tokens {
+            kept -= rest_tokens[k];
+            k += 1;
+        }
+
+        let mut rest = rest;
+        let kept_rest = rest.split_off(k);
+        let mut candidate = head;
+        if excerpt && k > 0 {
+            candidate.push((excerpt_message(&rest, excerpt_max, tokenizer), MessageMeta::now()));
+        }
+        candidate.extend(kept_rest);
+
+        self.set_entries(candidate);
+        report.removed = k;
+        report.excerpted = excerpt && k > 0;
+        report.tokens_after = self.estimate_tokens(tokenizer);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(n: usize) -> ConversationHistory {
+        let mut h = ConversationHistory::new();
+        h.add_user("task: original question");
+        for i in 0..n {
+            h.add_assistant(format!("assistant turn {i} {}", "x".repeat(40)));
+            h.add_function("tool", format!("{{\"i\":{i},\"pad\":\"{}\"}}", "y".repeat(40)));
+        }
+        h
+    }
+
+    #[test]
+    fn heuristic_counts_ascii_and_cjk() {
+        let t = CharHeuristicTokenizer;
+        assert_eq!(t.count_tokens("abcd"), 1);
+        assert_eq!(t.count_tokens("abcde"), 2);
+        assert_eq!(t.count_tokens("日本語"), 3);
+    }
+
+    #[test]
+    fn within_budget_is_untouched() {
+        let mut h = history(2);
+        let report = h.fit_to_budget(&ContextBudget::new(10_000, ContextStrategy::DropOldest));
+        assert!(!report.trimmed());
+        assert_eq!(h.len(), 5);
+    }
+
+    #[test]
+    fn drop_oldest_removes_first_user() {
+        let mut h = history(10);
+        let report = h.fit_to_budget(&ContextBudget::new(100, ContextStrategy::DropOldest));
+        assert!(report.trimmed());
+        assert!(report.tokens_after <= 100);
+        assert!(!message_text(&h.as_slice()[0]).starts_with("task:"));
+    }
+
+    #[test]
+    fn pin_first_user_keeps_task() {
+        let mut h = history(10);
+        let report = h.fit_to_budget(&ContextBudget::new(100, ContextStrategy::PinFirstUser));
+        assert!(report.tokens_after <= 100);
+        assert_eq!(message_text(&h.as_slice()[0]), "task: original question");
+        assert!(matches!(h.as_slice().last(), Some(ChatCompletionRequestMessage::Function(_))));
+    }
+
+    #[test]
+    fn excerpt_rolls_dropped_turns_into_one_message() {
+        let mut h = history(10);
+        let budget = ContextBudget::new(200, ContextStrategy::Excerpt);
+        let report = h.fit_to_budget(&budget);
+        assert!(report.excerpted);
+        assert!(report.tokens_after <= 200);
+        assert_eq!(message_text(&h.as_slice()[0]), "task: original question");
+        let excerpts = message_text(&h.as_slice()[1]);
+        assert!(excerpts.starts_with(CONTEXT_EXCERPT_PREFIX));
+        assert!(excerpts.contains("- assistant: assistant turn"));
+
+        // A second trim folds the earlier excerpts into the new message instead of nesting it.
+        for i in 10..20 {
+            h.add_assistant(format!("assistant turn {i} {}", "x".repeat(40)));
+        }
+        let report = h.fit_to_budget(&budget);
+        assert!(report.tokens_after <= 200);
+        let excerpts = message_text(&h.as_slice()[1]);
+        assert_eq!(excerpts.matches(CONTEXT_EXCERPT_PREFIX).count(), 1);
+        assert!(excerpts.contains("earlier items omitted"));
+    }
+
+    #[test]
+    fn each_message_is_counted_a_constant_number_of_times() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        #[derive(Default)]
+        struct Counting(AtomicUsize);
+        impl Tokenizer for Counting {
+            fn count_tokens(&self, text: &str) -> usize {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                CharHeuristicTokenizer.count_tokens(text)
+            }
+        }
+        let counter = Arc::new(Counting::default());
+        let mut h = history(200);
+        let n = h.len();
+        let report = h.fit_to_budget(&ContextBudget::new(100, ContextStrategy::PinFirstUser).with_tokenizer(counter.clone()));
+        assert!(report.removed > 350);
+        // before/after totals plus one pass over the candidates, not one pass per dropped message
+        assert!(counter.0.load(Ordering::Relaxed) <= 3 * n, "{} calls for {n} messages", counter.0.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn request_overhead_is_reserved_from_the_budget() {
+        let tools = [crate::openai::tools::build_add_tool()];
+        let overhead = estimate_request_overhead("system prompt", &tools, &CharHeuristicTokenizer);
+        assert!(overhead > estimate_request_overhead("system prompt", &[], &CharHeuristicTokenizer));
+
+        let budget = ContextBudget::new(300, ContextStrategy::PinFirstUser);
+        let reserved = budget.reserving(overhead);
+        assert_eq!(reserved.max_tokens, 300 - overhead);
+        let mut h = history(10);
+        assert!(h.fit_to_budget(&reserved).tokens_after + overhead <= 300);
+        assert_eq!(budget.reserving(1_000).max_tokens, 0);
+    }
+
+    #[test]
+    fn custom_tokenizer_is_used() {
+        struct Words;
+        impl Tokenizer for Words {
+            fn count_tokens(&self, text: &str) -> usize { text.split_whitespace().count() }
+        }
+        let h = history(1);
+        let budget = ContextBudget::default().with_tokenizer(Arc::new(Words));
+        // "task: original question" = 3 words + overhead
+        assert_eq!(estimate_message_tokens(&h.as_slice()[0], budget.tokenizer()), 3 + MESSAGE_OVERHEAD_TOKENS);
+    }
+}
@@ -1,21 +1,80 @@
 use async_openai::types::{
-    ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestFunctionMessageArgs,
-    ChatCompletionRequestMessage,ChatCompletionRequestUserMessageArgs,
+    ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestAssistantMessageContent,
+    ChatCompletionRequestAssistantMessageContentPart, ChatCompletionRequestDeveloperMessageContent,
+    ChatCompletionRequestFunctionMessageArgs, ChatCompletionRequestMessage,
+    ChatCompletionRequestSystemMessageContent, ChatCompletionRequestSystemMessageContentPart,
+    ChatCompletionRequestToolMessageContent, ChatCompletionRequestToolMessageContentPart,
+    ChatCompletionRequestUserMessageArgs, ChatCompletionRequestUserMessageContent,
+    ChatCompletionRequestUserMessageContentPart,
 };
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::history_format::HistoryDocument;
+
+/// Role of a message, independent of the async-openai enum layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    Developer,
+    System,
+    User,
+    Assistant,
+    Tool,
+    Function,
+}
+
+impl MessageRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::Developer => "developer",
+            MessageRole::System => "system",
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::Tool => "tool",
+            MessageRole::Function => "function",
+        }
+    }
+}
+
+impl Display for MessageRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// Metadata recorded alongside each message (not sent to the API).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageMeta {
+    /// When the message was added (UNIX epoch milliseconds).
+    pub timestamp_epoch_ms: i64,
+}
+
+impl MessageMeta {
+    /// Metadata stamped with the current time.
+    pub fn now() -> Self {
+        let ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0);
+        Self { timestamp_epoch_ms: ms }
+    }
+}
 
 /// Simple helper struct to build and reuse a conversation history (excluding the system message).
 /// This wraps a `Vec<ChatCompletionRequestMessage>` and provides ergonomic builder-style helpers.
 ///
 /// Invariant: System message is deliberately excluded; caller / higher level decides the system prompt.
 /// The order of messages is preserved (push order == send order).
-#[derive(Debug, Default, Clone)]
+/// `meta` is kept parallel to `messages` (same length, same order).
+///
+/// Serde uses the versioned format in `history_format` (see `to_json` / `from_json`).
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(into = "HistoryDocument", try_from = "HistoryDocument")]
 pub struct ConversationHistory {
     messages: Vec<ChatCompletionRequestMessage>,
+    meta: Vec<MessageMeta>,
 }
 
 impl ConversationHistory {
     /// Create empty history.
-    pub fn new() -> Self { Self { messages: Vec::new() } }
+    pub fn new() -> Self { Self { messages: Vec::new(), meta: Vec::new() } }
 
     /// Current length.
     pub fn len(&self) -> usize { self.messages.len() }
@@ -28,8 +87,90 @@ impl ConversationHistory {
     /// Consume and return inner vector.
     pub fn into_vec(self) -> Vec<ChatCompletionRequestMessage> { self.messages }
 
+    /// Text of the first user message, if any.
+    pub fn first_user_text(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .find(|m| matches!(m, ChatCompletionRequestMessage::User(_)))
+            .map(message_text)
+    }
+
+    /// Per-message metadata, parallel to `as_slice()`.
+    pub fn meta(&self) -> &[MessageMeta] { &self.meta }
+
+    /// Role of the message at `index`.
+    pub fn role(&self, index: usize) -> Option<MessageRole> { self.messages.get(index).map(message_role) }
+
+    /// Plain text of the message at `index` (see `message_text`).
+    pub fn text(&self, index: usize) -> Option<String> { self.messages.get(index).map(message_text) }
+
+    /// Roles of all messages in order.
+    pub fn roles(&self) -> Vec<MessageRole> { self.messages.iter().map(message_role).collect() }
+
+    /// Index of the most recent message with `role`.
+    pub fn last_index_of(&self, role: MessageRole) -> Option<usize> {
+        self.messages.iter().rposition(|m| message_role(m) == role)
+    }
+
+    /// Remove and return the last message.
+    pub fn pop(&mut self) -> Option<ChatCompletionRequestMessage> {
+        self.meta.pop();
+        self.messages.pop()
+    }
+
+    /// Remove everything after the last user message (the assistant answer and any tool results of
+    /// that turn), so the turn can be regenerated. Returns the number of removed messages;
+    /// without any user message nothing is removed (system or seeded messages stay).
+    pub fn pop_last_assistant_turn(&mut self) -> usize {
+        let Some(keep) = self.last_index_of(MessageRole::User).map(|i| i + 1) else {
+            return 0;
+        };
+        let removed = self.len().saturating_sub(keep);
+        self.truncate(keep);
+        removed
+    }
+
+    /// Keep only the first `len` messages (no-op if already shorter).
+    pub fn truncate(&mut self, len: usize) {
+        self.messages.truncate(len);
+        self.meta.truncate(len);
+    }
+
+    /// Replace the message at `index`, stamping it with the current time. Returns the old message,
+    /// or `None` (history unchanged) when `index` is out of range.
+    pub fn replace(&mut self, index: usize, msg: ChatCompletionRequestMessage) -> Option<ChatCompletionRequestMessage> {
+        let slot = self.messages.get_mut(index)?;
+        self.meta[index] = MessageMeta::now();
+        Some(std::mem::replace(slot, msg))
+    }
+
+    /// Fork a new history containing the first `len` messages (with their metadata).
+    /// The original is left untouched, e.g. to try an edited prompt while keeping the old thread.
+    pub fn branch(&self, len: usize) -> ConversationHistory {
+        let len = len.min(self.len());
+        Self { messages: self.messages[..len].to_vec(), meta: self.meta[..len].to_vec() }
+    }
+
     /// Push raw message (advanced use).
-    pub fn push(&mut self, msg: ChatCompletionRequestMessage) { self.messages.push(msg); }
+    pub fn push(&mut self, msg: ChatCompletionRequestMessage) { self.push_with_meta(msg, MessageMeta::now()); }
+
+    /// Push raw message with explicit metadata (e.g. when loading a saved history).
+    pub fn push_with_meta(&mut self, msg: ChatCompletionRequestMessage, meta: MessageMeta) {
+        self.messages.push(msg);
+        self.meta.push(meta);
+    }
+
+    /// Take all (message, meta) pairs out, leaving the history empty. Used by in-crate editing helpers.
+    pub(crate) fn take_entries(&mut self) -> Vec<(ChatCompletionRequestMessage, MessageMeta)> {
+        let messages = std::mem::take(&mut self.messages);
+        let meta = std::mem::take(&mut self.meta);
+        messages.into_iter().zip(meta).collect()
+    }
+
+    /// Replace all entries (counterpart of `take_entries`).
+    pub(crate) fn set_entries(&mut self, entries: Vec<(ChatCompletionRequestMessage, MessageMeta)>) {
+        (self.messages, self.meta) = entries.into_iter().unzip();
+    }
 
     /// Add user message.
     pub fn add_user<S: AsRef<str>>(&mut self, content: S) -> &mut Self {
@@ -37,7 +178,7 @@ impl ConversationHistory {
             .content(content.as_ref())
             .build()
             .expect("valid user message");
-        self.messages.push(msg.into());
+        self.push(msg.into());
         self
     }
 
@@ -47,7 +188,7 @@ impl ConversationHistory {
             .content(content.as_ref())
             .build()
             .expect("valid assistant message");
-        self.messages.push(msg.into());
+        self.push(msg.into());
         self
     }
 
@@ -58,14 +199,79 @@ impl ConversationHistory {
             .content(content.as_ref())
             .build()
             .expect("valid function message");
-        self.messages.push(msg.into());
+        self.push(msg.into());
         self
     }
 }
 
+/// Role of a message.
+pub(crate) fn message_role(msg: &ChatCompletionRequestMessage) -> MessageRole {
+    match msg {
+        ChatCompletionRequestMessage::Developer(_) => MessageRole::Developer,
+        ChatCompletionRequestMessage::System(_) => MessageRole::System,
+        ChatCompletionRequestMessage::User(_) => MessageRole::User,
+        ChatCompletionRequestMessage::Assistant(_) => MessageRole::Assistant,
+        ChatCompletionRequestMessage::Tool(_) => MessageRole::Tool,
+        ChatCompletionRequestMessage::Function(_) => MessageRole::Function,
+    }
+}
+
+/// Plain text of a message (text parts joined with newlines; non-text parts are skipped).
+pub(crate) fn message_text(msg: &ChatCompletionRequestMessage) -> String {
+    match msg {
+        ChatCompletionRequestMessage::Developer(m) => match &m.content {
+            ChatCompletionRequestDeveloperMessageContent::Text(t) => t.clone(),
+            ChatCompletionRequestDeveloperMessageContent::Array(parts) => {
+                parts.iter().map(|p| p.text.as_str()).collect::<Vec<_>>().join("\n")
+            }
+        },
+        ChatCompletionRequestMessage::System(m) => match &m.content {
+            ChatCompletionRequestSystemMessageContent::Text(t) => t.clone(),
+            ChatCompletionRequestSystemMessageContent::Array(parts) => parts
+                .iter()
+                .map(|ChatCompletionRequestSystemMessageContentPart::Text(p)| p.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+        ChatCompletionRequestMessage::User(m) => match &m.content {
+            ChatCompletionRequestUserMessageContent::Text(t) => t.clone(),
+            ChatCompletionRequestUserMessageContent::Array(parts) => parts
+                .iter()
+                .filter_map(|p| match p {
+                    ChatCompletionRequestUserMessageContentPart::Text(t) => Some(t.text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+        ChatCompletionRequestMessage::Assistant(m) => match &m.content {
+            Some(ChatCompletionRequestAssistantMessageContent::Text(t)) => t.clone(),
+            Some(ChatCompletionRequestAssistantMessageContent::Array(parts)) => parts
+                .iter()
+                .map(|p| match p {
+                    ChatCompletionRequestAssistantMessageContentPart::Text(t) => t.text.as_str(),
+                    ChatCompletionRequestAssistantMessageContentPart::Refusal(r) => r.refusal.as_str(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => m.refusal.clone().unwrap_or_default(),
+        },
+        ChatCompletionRequestMessage::Tool(m) => match &m.content {
+            ChatCompletionRequestToolMessageContent::Text(t) => t.clone(),
+            ChatCompletionRequestToolMessageContent::Array(parts) => parts
+                .iter()
+                .map(|ChatCompletionRequestToolMessageContentPart::Text(p)| p.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+        ChatCompletionRequestMessage::Function(m) => m.content.clone().unwrap_or_default(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_openai::types::ChatCompletionRequestSystemMessageArgs;
 
     #[test]
     fn build_and_length() {
@@ -80,11 +286,59 @@ mod tests {
     fn order_preserved() {
         let mut h = ConversationHistory::new();
         h.add_user("u1").add_assistant("a1").add_user("u2");
-        let slice = h.as_slice();
-        assert_eq!(slice.len(), 3);
-        // naive string extraction by debug format; we just ensure ordering by matching indices types
-        match &slice[0] { ChatCompletionRequestMessage::User(_) => {}, _ => panic!("expected user at 0") }
-        match &slice[1] { ChatCompletionRequestMessage::Assistant(_) => {}, _ => panic!("expected assistant at 1") }
-        match &slice[2] { ChatCompletionRequestMessage::User(_) => {}, _ => panic!("expected user at 2") }
+        assert_eq!(h.roles(), vec![MessageRole::User, MessageRole::Assistant, MessageRole::User]);
+        assert_eq!(h.text(0).as_deref(), Some("u1"));
+        assert_eq!(h.text(1).as_deref(), Some("a1"));
+        assert_eq!(h.text(2).as_deref(), Some("u2"));
+        assert_eq!(h.role(3), None);
+    }
+
+    #[test]
+    fn pop_last_assistant_turn_removes_answer_and_tool_results() {
+        let mut h = ConversationHistory::new();
+        h.add_user("u1").add_assistant("a1").add_user("u2");
+        h.add_function("add", "{\"sum\":3}").add_assistant("a2");
+        assert_eq!(h.pop_last_assistant_turn(), 2);
+        assert_eq!(h.len(), 3);
+        assert_eq!(h.meta().len(), 3);
+        assert_eq!(h.role(2), Some(MessageRole::User));
+        // Nothing to pop when the last message is already a user prompt.
+        assert_eq!(h.pop_last_assistant_turn(), 0);
+
+        // Without a user turn the history is left alone.
+        let mut seeded = ConversationHistory::new();
+        seeded.push(ChatCompletionRequestSystemMessageArgs::default().content("sys").build().unwrap().into());
+        seeded.add_assistant("greeting");
+        assert_eq!(seeded.pop_last_assistant_turn(), 0);
+        assert_eq!(seeded.len(), 2);
+    }
+
+    #[test]
+    fn truncate_replace_and_pop() {
+        let mut h = ConversationHistory::new();
+        h.add_user("u1").add_assistant("a1").add_user("u2");
+        let edited = ChatCompletionRequestUserMessageArgs::default().content("u2 (edited)").build().unwrap();
+        assert!(h.replace(2, edited.clone().into()).is_some());
+        assert_eq!(h.text(2).as_deref(), Some("u2 (edited)"));
+        assert!(h.replace(9, edited.into()).is_none());
+
+        assert_eq!(h.pop().map(|m| message_text(&m)), Some("u2 (edited)".to_string()));
+        h.truncate(1);
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.meta().len(), 1);
+        h.truncate(5);
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn branch_is_independent() {
+        let mut h = ConversationHistory::new();
+        h.add_user("u1").add_assistant("a1").add_user("u2");
+        let mut b = h.branch(2);
+        b.add_user("u2 alt");
+        assert_eq!(h.text(2).as_deref(), Some("u2"));
+        assert_eq!(b.text(2).as_deref(), Some("u2 alt"));
+        assert_eq!(b.meta()[0], h.meta()[0]);
+        assert_eq!(h.branch(10).len(), 3);
     }
 }
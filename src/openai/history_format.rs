@@ -0,0 +1,308 @@
+//! Stable, versioned JSON format for `ConversationHistory` plus a Markdown transcript export.
+//!
+//! The format does not depend on async-openai's serde layout, so saved files keep loading
+//! across SDK upgrades:
+//!
+//! ```json
+//! {
+//!   "format": "rust_test.conversation",
+//!   "version": 1,
+//!   "exported_at_epoch_ms": 1700000000000,
+//!   "messages": [
+//!     { "role": "user", "content": "hi", "timestamp_epoch_ms": 1700000000000 },
+//!     { "role": "assistant", "tool_calls": [{ "id": "c1", "name": "add", "arguments": "{}" }], "timestamp_epoch_ms": 1700000000001 }
+//!   ]
+//! }
+//! ```
+
+use async_openai::types::{
+    ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessageArgs,
+    ChatCompletionRequestDeveloperMessageArgs, ChatCompletionRequestFunctionMessageArgs,
+    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
+    ChatCompletionRequestToolMessageArgs, ChatCompletionRequestUserMessageArgs,
+    ChatCompletionToolType, FunctionCall,
+};
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use super::history::{message_role, message_text, ConversationHistory, MessageMeta, MessageRole};
+
+/// Identifier written to the `format` field.
+pub const HISTORY_FORMAT: &str = "rust_test.conversation";
+/// Current format version. Bump when the layout changes incompatibly.
+pub const HISTORY_FORMAT_VERSION: u32 = 1;
+
+/// Top-level document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryDocument {
+    pub format: String,
+    pub version: u32,
+    pub exported_at_epoch_ms: i64,
+    pub messages: Vec<MessageRecord>,
+}
+
+/// One message in the saved format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageRecord {
+    pub role: MessageRole,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Function name (function role) or participant name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCallRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    pub timestamp_epoch_ms: i64,
+}
+
+/// Tool call requested by an assistant message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+impl MessageRecord {
+    fn from_message(msg: &ChatCompletionRequestMessage, meta: &MessageMeta) -> Self {
+        let role = message_role(msg);
+        let text = message_text(msg);
+        let mut record = MessageRecord {
+            role,
+            content: Some(text),
+            name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            timestamp_epoch_ms: meta.timestamp_epoch_ms,
+        };
+        match msg {
+            ChatCompletionRequestMessage::Developer(m) => record.name = m.name.clone(),
+            ChatCompletionRequestMessage::System(m) => record.name = m.name.clone(),
+            ChatCompletionRequestMessage::User(m) => record.name = m.name.clone(),
+            ChatCompletionRequestMessage::Assistant(m) => {
+                if m.content.is_none() && m.refusal.is_none() { record.content = None; }
+                record.name = m.name.clone();
+                record.tool_calls = m
+                    .tool_calls
+                    .iter()
+                    .flatten()
+                    .map(|c| ToolCallRecord {
+                        id: c.id.clone(),
+                        name: c.function.name.clone(),
+                        arguments: c.function.arguments.clone(),
+                    })
+                    .collect();
+            }
+            ChatCompletionRequestMessage::Tool(m) => record.tool_call_id = Some(m.tool_call_id.clone()),
+            ChatCompletionRequestMessage::Function(m) => {
+                if m.content.is_none() { record.content = None; }
+                record.name = Some(m.name.clone());
+            }
+        }
+        record
+    }
+
+    fn to_message(&self) -> Result<ChatCompletionRequestMessage> {
+        let content = self.content.clone().unwrap_or_default();
+        let msg: ChatCompletionRequestMessage = match self.role {
+            MessageRole::Developer => {
+                let mut b = ChatCompletionRequestDeveloperMessageArgs::default();
+                b.content(content);
+                if let Some(n) = &self.name { b.name(n); }
+                b.build()?.into()
+            }
+            MessageRole::System => {
+                let mut b = ChatCompletionRequestSystemMessageArgs::default();
+                b.content(content);
+                if let Some(n) = &self.name { b.name(n); }
+                b.build()?.into()
+            }
+            MessageRole::User => {
+                let mut b = ChatCompletionRequestUserMessageArgs::default();
+                b.content(content);
+                if let Some(n) = &self.name { b.name(n); }
+                b.build()?.into()
+            }
+            MessageRole::Assistant => {
+                let mut b = ChatCompletionRequestAssistantMessageArgs::default();
+                if let Some(c) = &self.content { b.content(c.as_str()); }
+                if let Some(n) = &self.name { b.name(n); }
+                if !self.tool_calls.is_empty() {
+                    b.tool_calls(
+                        self.tool_calls
+                            .iter()
+                            .map(|c| ChatCompletionMessageToolCall {
+                                id: c.id.clone(),
+                                r#type: ChatCompletionToolType::Function,
+                                function: FunctionCall { name: c.name.clone(), arguments: c.arguments.clone() },
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                b.build()?.into()
+            }
+            MessageRole::Tool => {
+                let id = self.tool_call_id.clone().ok_or_else(|| eyre!("tool message without tool_call_id"))?;
+                ChatCompletionRequestToolMessageArgs::default()
+                    .content(content)
+                    .tool_call_id(id)
+                    .build()?
+                    .into()
+            }
+            MessageRole::Function => {
+                let name = self.name.clone().ok_or_else(|| eyre!("function message without name"))?;
+                let mut b = ChatCompletionRequestFunctionMessageArgs::default();
+                b.name(name);
+                if let Some(c) = &self.content { b.content(c.as_str()); }
+                b.build()?.into()
+            }
+        };
+        Ok(msg)
+    }
+}
+
+impl From<ConversationHistory> for HistoryDocument {
+    fn from(history: ConversationHistory) -> Self {
+        HistoryDocument::from(&history)
+    }
+}
+
+impl From<&ConversationHistory> for HistoryDocument {
+    fn from(history: &ConversationHistory) -> Self {
+        HistoryDocument {
+            format: HISTORY_FORMAT.to_string(),
+            version: HISTORY_FORMAT_VERSION,
+            exported_at_epoch_ms: MessageMeta::now().timestamp_epoch_ms,
+            messages: history
+                .as_slice()
+                .iter()
+                .zip(history.meta())
+                .map(|(m, meta)| MessageRecord::from_message(m, meta))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<HistoryDocument> for ConversationHistory {
+    type Error = color_eyre::Report;
+
+    fn try_from(doc: HistoryDocument) -> Result<Self> {
+        if doc.format != HISTORY_FORMAT {
+            return Err(eyre!("unsupported history format '{}' (expected '{HISTORY_FORMAT}')", doc.format));
+        }
+        if doc.version == 0 || doc.version > HISTORY_FORMAT_VERSION {
+            return Err(eyre!("unsupported history version {} (this build reads up to {HISTORY_FORMAT_VERSION})", doc.version));
+        }
+        let mut history = ConversationHistory::new();
+        for (i, record) in doc.messages.iter().enumerate() {
+            let msg = record.to_message().map_err(|e| eyre!("message #{i}: {e}"))?;
+            history.push_with_meta(msg, MessageMeta { timestamp_epoch_ms: record.timestamp_epoch_ms });
+        }
+        Ok(history)
+    }
+}
+
+impl ConversationHistory {
+    /// Serialize to the versioned JSON format (pretty printed).
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&HistoryDocument::from(self))?)
+    }
+
+    /// Load from the versioned JSON format.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let doc: HistoryDocument = serde_json::from_str(json)?;
+        ConversationHistory::try_from(doc)
+    }
+
+    /// Human-readable transcript for bug reports. Function/tool results that are JSON are pretty printed.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Conversation ({} messages)\n", self.len());
+        for (i, (msg, meta)) in self.as_slice().iter().zip(self.meta()).enumerate() {
+            let record = MessageRecord::from_message(msg, meta);
+            let title = match (&record.role, &record.name) {
+                (MessageRole::Function, Some(name)) => format!("function `{name}`"),
+                (MessageRole::Tool, _) => format!("tool ({})", record.tool_call_id.as_deref().unwrap_or("?")),
+                (role, _) => role.to_string(),
+            };
+            out.push_str(&format!("\n## {}. {title}\n\n_epoch_ms: {}_\n\n", i + 1, record.timestamp_epoch_ms));
+            if let Some(content) = record.content.as_deref().filter(|c| !c.is_empty()) {
+                let is_result = matches!(record.role, MessageRole::Function | MessageRole::Tool);
+                match serde_json::from_str::<serde_json::Value>(content) {
+                    Ok(v) if is_result => {
+                        let pretty = serde_json::to_string_pretty(&v).unwrap_or_else(|_| content.to_string());
+                        out.push_str(&format!("```json\n{pretty}\n```\n"));
+                    }
+                    _ => out.push_str(&format!("{content}\n")),
+                }
+            }
+            for call in &record.tool_calls {
+                out.push_str(&format!("\n- tool call `{}` (id `{}`): `{}`\n", call.name, call.id, call.arguments));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ConversationHistory {
+        let mut h = ConversationHistory::new();
+        h.add_user("2+3?").add_assistant("calling add").add_function("add", r#"{"sum":5}"#).add_assistant("5");
+        let call = ChatCompletionRequestAssistantMessageArgs::default()
+            .tool_calls(vec![ChatCompletionMessageToolCall {
+                id: "call_1".into(),
+                r#type: ChatCompletionToolType::Function,
+                function: FunctionCall { name: "add".into(), arguments: r#"{"x":2,"y":3}"#.into() },
+            }])
+            .build()
+            .unwrap();
+        h.push(call.into());
+        h.push(ChatCompletionRequestToolMessageArgs::default().content("ok").tool_call_id("call_1").build().unwrap().into());
+        h
+    }
+
+    #[test]
+    fn json_round_trip_preserves_messages_and_meta() {
+        let h = sample();
+        let json = h.to_json().unwrap();
+        let back = ConversationHistory::from_json(&json).unwrap();
+        assert_eq!(back.as_slice(), h.as_slice());
+        assert_eq!(back.meta(), h.meta());
+    }
+
+    #[test]
+    fn format_is_stable() {
+        let v: serde_json::Value = serde_json::from_str(&sample().to_json().unwrap()).unwrap();
+        assert_eq!(v["format"], HISTORY_FORMAT);
+        assert_eq!(v["version"], HISTORY_FORMAT_VERSION);
+        assert_eq!(v["messages"][0]["role"], "user");
+        assert_eq!(v["messages"][0]["content"], "2+3?");
+        assert_eq!(v["messages"][2]["name"], "add");
+        assert_eq!(v["messages"][4]["tool_calls"][0]["name"], "add");
+        assert!(v["messages"][4].get("content").is_none());
+        assert_eq!(v["messages"][5]["tool_call_id"], "call_1");
+        assert!(v["messages"][0]["timestamp_epoch_ms"].as_i64().unwrap() > 0);
+    }
+
+    #[test]
+    fn rejects_unknown_version_and_format() {
+        let future = r#"{"format":"rust_test.conversation","version":99,"exported_at_epoch_ms":0,"messages":[]}"#;
+        assert!(ConversationHistory::from_json(future).unwrap_err().to_string().contains("version 99"));
+        let other = r#"{"format":"other","version":1,"exported_at_epoch_ms":0,"messages":[]}"#;
+        assert!(ConversationHistory::from_json(other).is_err());
+    }
+
+    #[test]
+    fn markdown_transcript() {
+        let md = sample().to_markdown();
+        assert!(md.starts_with("# Conversation (6 messages)"));
+        assert!(md.contains("## 1. user"));
+        assert!(md.contains("## 3. function `add`"));
+        assert!(md.contains("```json\n{\n  \"sum\": 5\n}\n```"));
+        assert!(md.contains("tool call `add` (id `call_1`)"));
+    }
+}
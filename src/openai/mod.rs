@@ -5,9 +5,11 @@ pub mod simple;
 pub mod call; // tool-calling (types, proposer, resolver, multi-step)
 pub mod tools; // consolidated tools (core, docs, tavily, constants)
 pub mod history; // conversation history helper
+pub mod context; // context-window (token budget) management for history
+pub mod history_format; // versioned JSON / Markdown export of history
 
 // 代表的な公開APIを再エクスポート
-pub use worker::start_openai_worker;
+pub use worker::{start_openai_worker, SessionId, WorkerMessage, WorkerRequest, WorkerSettings};
 pub use simple::{
 	get_ai_answer_once,
 	get_ai_answer_once_blocking,	
@@ -24,8 +26,21 @@ pub use call::{
 	multi_step_tool_answer_blocking,
 	multi_step_tool_answer_with_logger,
 	multi_step_tool_answer_blocking_with_logger,
+	multi_step_tool_answer_with_history,
+	multi_step_tool_answer_with_history_and_logger,
+	OversizeStrategy,
+	ToolResultLimits,
+};
+pub use history::{ConversationHistory, MessageMeta, MessageRole};
+pub use history_format::{HistoryDocument, HISTORY_FORMAT_VERSION};
+pub use context::{
+	CharHeuristicTokenizer,
+	ContextBudget,
+	ContextStrategy,
+	estimate_request_overhead,
+	Tokenizer,
+	TrimReport,
 };
-pub use history::ConversationHistory;
 pub use tools::{
 	ToolDefinition,
 	ToolHandler,
@@ -37,9 +52,18 @@ pub use tools::{
 	build_tavily_search_tool,
 	tavily_search,
 	build_number_guess_tool,
+	RpgSessions,
+	DEFAULT_GAME_ID,
+	build_rpg_new_game_tool,
+	build_rpg_end_game_tool,
 	build_rpg_get_rules_tool,
 	build_rpg_get_state_tool,
 	build_rpg_list_actions_tool,
 	build_rpg_issue_action_tool,
 	build_rpg_tools,
+	build_rpg_tools_with,
+	build_tool_set,
+	build_tool_sets,
+	build_tool_sets_with_rpg,
+	TOOL_SET_NAMES,
 };
@@ -1,11 +1,13 @@
 use std::sync::Arc;
 use serde_json::{json, Value};
+use crate::openai::call::{truncate_str, DEFAULT_TOOL_RESULT_MAX_BYTES};
 use crate::openai::tools::{ToolDefinition, ToolParametersBuilder};
 
 /// docs フォルダ配下（サブフォルダ含む）の Markdown / テキストを返すツール。
 /// - 入力は `path`（docs からの相対パス）。例: "benches.md", "guides/intro.md"
 /// - `.md` と `.txt` のみ許可。
 /// - `std::fs::canonicalize` でパストラバーサルを防止し、`docs` 配下であることを検証。
+///
 /// 返却形式: { "path": string, "filename": string, "content": string, ("truncated": bool)? } または { "error": string }
 pub fn build_read_doc_tool() -> ToolDefinition {
     let parameters = ToolParametersBuilder::new_object()
@@ -22,10 +24,13 @@ pub fn build_read_doc_tool() -> ToolDefinition {
     )
 }
 
+/// Size cap for the whole JSON result (escaping and wrapper fields included). It matches the
+/// multi-step loop's default tool-result cap, so the loop never has to cut the JSON mid-string.
+const MAX_RESULT_BYTES: usize = DEFAULT_TOOL_RESULT_MAX_BYTES;
+
 /// Implementation separated from the closure to allow easier testing / reuse.
 fn read_docs_file_impl(args: &Value) -> color_eyre::Result<Value> {
     use std::path::{Path, PathBuf};
-    const MAX_BYTES: usize = 16 * 1024; // 16KB safety limit
 
     // 1) Parse input path
     let rel_path_str = match args.get("path").and_then(|v| v.as_str()) {
@@ -73,28 +78,32 @@ fn read_docs_file_impl(args: &Value) -> color_eyre::Result<Value> {
         Err(e) => return Ok(json!({"error": format!("read error: {e}")})),
     };
 
-    // 7) Truncate if needed
-    let mut truncated = false;
-    let output = if content.len() > MAX_BYTES {
-        truncated = true;
-        let mut s = content.clone();
-        s.truncate(MAX_BYTES);
-        s
-    } else { content };
-
-    // 8) Prepare response: keep backward-compatible "filename" and add "path"
+    // 7) Prepare response: keep backward-compatible "filename" and add "path"
     let filename = candidate_canon.file_name().and_then(|n| n.to_str()).unwrap_or("");
-    let rel_display = rel_path.to_string_lossy().to_string();
-    if truncated {
-        Ok(json!({
-            "path": rel_display,
+    Ok(docs_response(&rel_path.to_string_lossy(), filename, &content))
+}
+
+/// Build the result, cutting `content` until the serialized JSON fits `MAX_RESULT_BYTES`.
+fn docs_response(path: &str, filename: &str, content: &str) -> Value {
+    let full = json!({ "path": path, "filename": filename, "content": content });
+    if full.to_string().len() <= MAX_RESULT_BYTES {
+        return full;
+    }
+    let mut keep = content.len().min(MAX_RESULT_BYTES);
+    loop {
+        let out = json!({
+            "path": path,
             "filename": filename,
-            "content": output,
+            "content": truncate_str(content, keep),
             "truncated": true,
-            "max_bytes": MAX_BYTES
-        }))
-    } else {
-        Ok(json!({ "path": rel_display, "filename": filename, "content": output }))
+            "max_bytes": MAX_RESULT_BYTES
+        });
+        // Escaping makes every content byte cost at least one output byte, so cutting the excess converges
+        let len = out.to_string().len();
+        if len <= MAX_RESULT_BYTES || keep == 0 {
+            return out;
+        }
+        keep = keep.saturating_sub(len - MAX_RESULT_BYTES);
     }
 }
 
@@ -108,7 +117,7 @@ mod tests {
         let tool = build_read_doc_tool();
         let out = tool.execute(&json!({"path": "benches.md"}))?;
         assert_eq!(out["filename"], "benches.md");
-        assert!(out["content"].as_str().unwrap_or("").len() > 0);
+        assert!(!out["content"].as_str().unwrap_or("").is_empty());
         Ok(())
     }
 
@@ -119,4 +128,21 @@ mod tests {
         assert!(err.contains("escapes docs root") || err.contains("absolute path not allowed") || err.contains("file not found"));
         Ok(())
     }
+
+    #[test]
+    fn large_content_fits_the_result_cap_after_escaping() {
+        // Quotes and newlines double in size when escaped
+        let content = "\"quoted\"\n".repeat(2_000);
+        let out = docs_response("big.md", "big.md", &content);
+        let text = out.to_string();
+        assert!(text.len() <= MAX_RESULT_BYTES, "{} bytes", text.len());
+        assert_eq!(out["truncated"], true);
+        assert!(content.starts_with(out["content"].as_str().unwrap()));
+        // Still valid JSON after the loop's size policy
+        let limited = crate::openai::call::limit_tool_result(&Default::default(), "read_docs_file", &text);
+        assert!(limited.applied.is_none());
+
+        let small = docs_response("a.md", "a.md", "hi");
+        assert!(small.get("truncated").is_none());
+    }
 }
@@ -9,6 +9,7 @@ mod docs; // docs reading tool
 mod tavily; // tavily search tool
 mod number_guess; // number guessing game tool
 mod rpg; // RPG game tools
+mod sets; // named tool sets (CLI / config selection)
 
 pub use core::{
     ToolDefinition,
@@ -21,9 +22,15 @@ pub use tavily::{build_tavily_search_tool, tavily_search};
 pub use sample_tools::{build_get_constants_tool, build_add_tool};
 pub use number_guess::build_number_guess_tool;
 pub use rpg::{
+    RpgSessions,
+    DEFAULT_GAME_ID,
+    build_rpg_new_game_tool,
+    build_rpg_end_game_tool,
     build_rpg_get_rules_tool,
     build_rpg_get_state_tool,
     build_rpg_list_actions_tool,
     build_rpg_issue_action_tool,
     build_rpg_tools,
-};
\ No newline at end of file
+    build_rpg_tools_with,
+};
+pub use sets::{build_tool_set, build_tool_sets, build_tool_sets_with_rpg, TOOL_SET_NAMES};
@@ -33,7 +33,7 @@ pub fn build_number_guess_tool(target: u32, max: u32) -> ToolDefinition {
                 .and_then(|val| val.as_i64())
                 .ok_or_else(|| color_eyre::eyre::eyre!("Invalid or missing 'guess' parameter"))?;
 
-            if !(1..=(max as i64)).contains(&(guess as i64)) {
+            if !(1..=(max as i64)).contains(&guess) {
                 return Ok(json!({ "result": "out_of_range" }));
             }
 
@@ -1,85 +1,197 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use lazy_static::lazy_static;
 use serde_json::{json, Value};
 
-use crate::rpg::{Game, Command, RpgRules};
+use crate::rpg::{player_name_from_env, Game, Command, RpgRules};
 use super::{ToolDefinition, ToolParametersBuilder};
 
-// Provide a single shared game instance for tool-driven play.
-// In a larger app, you might manage sessions keyed by an ID.
-lazy_static! {
-    static ref GAME: Mutex<Game> = Mutex::new(Game::new());
+/// Game used when a tool call omits `game_id`.
+pub const DEFAULT_GAME_ID: &str = "default";
+
+/// Independent games keyed by id, shared by the tools built from the same store.
+///
+/// Cloning shares the store. Every `build_rpg_tools` call gets a fresh one, so parallel tests,
+/// agents and TUI tabs never play in each other's game; use `build_rpg_tools_with` to share.
+#[derive(Clone, Default)]
+pub struct RpgSessions {
+    inner: Arc<Mutex<RpgSessionsInner>>,
 }
 
-/// Tool: Return the RPG rules/config as JSON.
-pub fn build_rpg_get_rules_tool() -> ToolDefinition {
+#[derive(Default)]
+struct RpgSessionsInner {
+    games: HashMap<String, Game>,
+    next_id: u64,
+}
+
+impl RpgSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a game (random seed unless given) and return its id.
+    /// The player is named from `RPG_PLAYER` either way; the name does not affect the RNG.
+    pub fn new_game(&self, seed: Option<u64>) -> String {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let id = format!("game-{}", inner.next_id);
+        let game = seed
+            .map_or_else(|| Game::with_rules(RpgRules::default()), Game::with_seed)
+            .with_player_name(player_name_from_env());
+        inner.games.insert(id.clone(), game);
+        id
+    }
+
+    /// Remove a game. Returns false when the id is unknown.
+    pub fn end_game(&self, id: &str) -> bool {
+        self.inner.lock().unwrap().games.remove(id).is_some()
+    }
+
+    /// Ids of the running games (sorted).
+    pub fn game_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.inner.lock().unwrap().games.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Run `f` on a game. `None` means the default game, which is created on first use.
+    pub fn with_game<T>(&self, id: Option<&str>, f: impl FnOnce(&mut Game) -> T) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap();
+        let game = match id {
+            Some(id) => inner.games.get_mut(id)?,
+            None => inner.games.entry(DEFAULT_GAME_ID.to_string()).or_default(),
+        };
+        Some(f(game))
+    }
+}
+
+/// Schema for tools that act on one game: an optional `game_id` plus `extra` properties.
+fn game_params(extra: impl FnOnce(ToolParametersBuilder) -> ToolParametersBuilder) -> ToolParametersBuilder {
+    extra(ToolParametersBuilder::new_object().add_string(
+        "game_id",
+        Some("Game id returned by rpg_new_game. Omit to use this tool set's default game."),
+    ))
+    .additional_properties(false)
+}
+
+/// Run `f` on the game named by `args.game_id`, or report an unknown id as a JSON error.
+fn on_game(
+    sessions: &RpgSessions,
+    args: &Value,
+    f: impl FnOnce(&mut Game) -> color_eyre::Result<Value>,
+) -> color_eyre::Result<Value> {
+    let id = args.get("game_id").and_then(|v| v.as_str());
+    sessions
+        .with_game(id, f)
+        .unwrap_or_else(|| Ok(json!({"error": format!("unknown game_id: {}", id.unwrap_or_default())})))
+}
+
+/// Tool: Start a new isolated game and return its id.
+pub fn build_rpg_new_game_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
     ToolDefinition::new(
-        "rpg_get_rules",
-        "Return the RPG rules/configuration so the model can understand game mechanics.",
+        "rpg_new_game",
+        "Start a new, independent RPG game. Pass the returned game_id to the other rpg_* tools. Give a seed to replay the same game.",
         ToolParametersBuilder::new_object()
+            .add_integer("seed", Some("Optional RNG seed; the same seed and actions give the same battles"), Some(0), None)
             .additional_properties(false)
             .build(),
-        Arc::new(|_args| {
-            let game = GAME.lock().unwrap();
-            let rules: &RpgRules = &game.rules;
-            Ok(serde_json::to_value(rules.clone())?)
+        Arc::new(move |args: &Value| {
+            let seed = args.get("seed").and_then(|v| v.as_u64());
+            let id = sessions.new_game(seed);
+            let snap = sessions.with_game(Some(&id), |game| game.snapshot()).expect("game just created");
+            Ok(json!({
+                "game_id": id,
+                "snapshot": serde_json::to_value(snap)?
+            }))
         })
     )
 }
 
-/// Tool: Return the current game state snapshot.
-pub fn build_rpg_get_state_tool() -> ToolDefinition {
+/// Tool: End a game and forget its state.
+pub fn build_rpg_end_game_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
     ToolDefinition::new(
-        "rpg_get_state",
-        "Return the current game state (player, enemy, turn, counters, rules subset).",
+        "rpg_end_game",
+        "End an RPG game started with rpg_new_game and discard its state.",
         ToolParametersBuilder::new_object()
+            .add_string("game_id", Some("Game id returned by rpg_new_game"))
+            .required("game_id")
             .additional_properties(false)
             .build(),
-        Arc::new(|_args| {
-            let game = GAME.lock().unwrap();
-            let snap = game.snapshot();
-            Ok(serde_json::to_value(snap)?)
+        Arc::new(move |args: &Value| {
+            let id = args.get("game_id").and_then(|v| v.as_str()).unwrap_or("");
+            Ok(json!({"game_id": id, "ended": sessions.end_game(id)}))
+        })
+    )
+}
+
+/// Tool: Return the RPG rules/config as JSON.
+pub fn build_rpg_get_rules_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
+    ToolDefinition::new(
+        "rpg_get_rules",
+        "Return the RPG rules/configuration so the model can understand game mechanics.",
+        game_params(|p| p).build(),
+        Arc::new(move |args: &Value| {
+            on_game(&sessions, args, |game| {
+                let rules: &RpgRules = &game.rules;
+                Ok(serde_json::to_value(rules.clone())?)
+            })
+        })
+    )
+}
+
+/// Tool: Return the current game state snapshot.
+pub fn build_rpg_get_state_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
+    ToolDefinition::new(
+        "rpg_get_state",
+        "Return the current game state (player, enemy, turn, counters, rules subset).",
+        game_params(|p| p).build(),
+        Arc::new(move |args: &Value| {
+            on_game(&sessions, args, |game| Ok(serde_json::to_value(game.snapshot())?))
         })
     )
 }
 
 /// Tool: List available player actions at this moment.
-pub fn build_rpg_list_actions_tool() -> ToolDefinition {
+pub fn build_rpg_list_actions_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
     ToolDefinition::new(
         "rpg_list_actions",
         "List available actions the player can take now.",
-        ToolParametersBuilder::new_object()
-            .additional_properties(false)
-            .build(),
-        Arc::new(|_args| {
-            let game = GAME.lock().unwrap();
-            let mut actions = vec!["attack", "heal", "run", "quit"]; // static for now
-            // Optional: filter by context, e.g., if zero potions, still allow heal but note it.
-            if game.is_over() {
-                actions = vec!["quit"];
-            }
-            Ok(json!({"actions": actions}))
+        game_params(|p| p).build(),
+        Arc::new(move |args: &Value| {
+            on_game(&sessions, args, |game| {
+                let mut actions = vec!["attack", "heal", "run", "quit"]; // static for now
+                // Optional: filter by context, e.g., if zero potions, still allow heal but note it.
+                if game.is_over() {
+                    actions = vec!["quit"];
+                }
+                Ok(json!({"actions": actions}))
+            })
         })
     )
 }
 
-/// Tool: Issue an action command. Returns updated snapshot and a simple log.
-pub fn build_rpg_issue_action_tool() -> ToolDefinition {
-    let params = ToolParametersBuilder::new_object()
-        .add_string_enum(
+/// Tool: Issue an action command. Returns the game events (structured and as text) and the updated snapshot.
+pub fn build_rpg_issue_action_tool(sessions: &RpgSessions) -> ToolDefinition {
+    let sessions = sessions.clone();
+    let params = game_params(|p| {
+        p.add_string_enum(
             "action",
             Some("One of: attack, heal, run, quit"),
             &["attack", "heal", "run", "quit"],
         )
         .required("action")
-        .additional_properties(false)
-        .build();
+    })
+    .build();
 
     ToolDefinition::new(
         "rpg_issue_action",
-        "Execute a player action and return the updated state.",
+        "Execute a player action. Returns the events it caused (damage, heal, flee, loot, new enemy, defeat) and the updated state.",
         params,
-        Arc::new(|args: &Value| {
+        Arc::new(move |args: &Value| {
             let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
             let cmd = match action {
                 "attack" => Command::Attack,
@@ -89,23 +201,109 @@ pub fn build_rpg_issue_action_tool() -> ToolDefinition {
                 _ => return Ok(json!({"error": "invalid action"})),
             };
 
-            let mut game = GAME.lock().unwrap();
-            let cont = game.handle_command(cmd).unwrap_or(true);
-            let snap = game.snapshot();
-            Ok(json!({
-                "continued": cont,
-                "snapshot": serde_json::to_value(snap)?
-            }))
+            on_game(&sessions, args, |game| {
+                let events = game.handle_command(cmd);
+                let log: Vec<String> = events.iter().map(ToString::to_string).collect();
+                let snap = game.snapshot();
+                Ok(json!({
+                    "continued": cmd != Command::Quit,
+                    "events": serde_json::to_value(&events)?,
+                    "log": log,
+                    "snapshot": serde_json::to_value(snap)?
+                }))
+            })
         })
     )
 }
 
-/// Convenience: return all RPG-related tools as a single vector.
+/// Convenience: return all RPG-related tools bound to a fresh, isolated game store.
 pub fn build_rpg_tools() -> Vec<ToolDefinition> {
+    build_rpg_tools_with(&RpgSessions::new())
+}
+
+/// All RPG-related tools playing in `sessions` (keep the store to continue the same games later).
+pub fn build_rpg_tools_with(sessions: &RpgSessions) -> Vec<ToolDefinition> {
     vec![
-        build_rpg_get_rules_tool(),
-        build_rpg_get_state_tool(),
-        build_rpg_list_actions_tool(),
-        build_rpg_issue_action_tool(),
+        build_rpg_new_game_tool(sessions),
+        build_rpg_end_game_tool(sessions),
+        build_rpg_get_rules_tool(sessions),
+        build_rpg_get_state_tool(sessions),
+        build_rpg_list_actions_tool(sessions),
+        build_rpg_issue_action_tool(sessions),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool<'a>(tools: &'a [ToolDefinition], name: &str) -> &'a ToolDefinition {
+        tools.iter().find(|t| t.name == name).unwrap()
+    }
+
+    #[test]
+    fn issue_action_returns_events_and_log() {
+        let tool = build_rpg_issue_action_tool(&RpgSessions::new());
+        let result = tool.execute(&json!({"action": "attack"})).unwrap();
+        assert_eq!(result["continued"], true);
+        let events = result["events"].as_array().unwrap();
+        assert_eq!(events[0]["type"], "damage_dealt");
+        assert_eq!(result["log"].as_array().unwrap().len(), events.len());
+
+        let quit = tool.execute(&json!({"action": "quit"})).unwrap();
+        assert_eq!(quit["continued"], false);
+        assert_eq!(quit["events"], json!([{"type": "quit"}]));
+    }
+
+    #[test]
+    fn games_are_isolated_by_id_and_by_tool_set() {
+        let tools = build_rpg_tools();
+        let a = tool(&tools, "rpg_new_game").execute(&json!({"seed": 42})).unwrap()["game_id"].clone();
+        let b = tool(&tools, "rpg_new_game").execute(&json!({"seed": 42})).unwrap()["game_id"].clone();
+        assert_ne!(a, b);
+
+        let issue = tool(&tools, "rpg_issue_action");
+        issue.execute(&json!({"game_id": a, "action": "attack"})).unwrap();
+        let state = tool(&tools, "rpg_get_state");
+        let state_a = state.execute(&json!({"game_id": a})).unwrap();
+        let state_b = state.execute(&json!({"game_id": b})).unwrap();
+        assert_eq!(state_a["seed"], 42);
+        assert!(state_a["enemy"]["hp"].as_i64() < state_b["enemy"]["hp"].as_i64());
+
+        // Another build_rpg_tools call has its own store, so the same id is unknown there
+        let other = build_rpg_tools();
+        let missing = tool(&other, "rpg_get_state").execute(&json!({"game_id": a})).unwrap();
+        assert!(missing["error"].as_str().unwrap().contains("unknown game_id"));
+    }
+
+    #[test]
+    fn end_game_forgets_the_game_and_default_game_is_created_on_demand() {
+        let sessions = RpgSessions::new();
+        let tools = build_rpg_tools_with(&sessions);
+        let id = sessions.new_game(Some(1));
+        assert_eq!(sessions.game_ids(), [id.as_str()]);
+
+        let ended = tool(&tools, "rpg_end_game").execute(&json!({"game_id": id})).unwrap();
+        assert_eq!(ended["ended"], true);
+        assert!(sessions.game_ids().is_empty());
+        let again = tool(&tools, "rpg_end_game").execute(&json!({"game_id": id})).unwrap();
+        assert_eq!(again["ended"], false);
+
+        let actions = tool(&tools, "rpg_list_actions").execute(&json!({})).unwrap();
+        assert!(actions["actions"].as_array().unwrap().contains(&json!("attack")));
+        assert_eq!(sessions.game_ids(), [DEFAULT_GAME_ID]);
+    }
+
+    #[test]
+    fn seeded_and_random_games_name_the_player_the_same_way() {
+        let sessions = RpgSessions::new();
+        let seeded = sessions.new_game(Some(1));
+        let random = sessions.new_game(None);
+        let name = |id: &str| sessions.with_game(Some(id), |g| g.player().name.clone()).unwrap();
+        assert_eq!(name(&seeded), player_name_from_env());
+        assert_eq!(name(&random), player_name_from_env());
+        // Renaming leaves the seeded game identical to a plain seeded one
+        let enemy_hp = sessions.with_game(Some(&seeded), |g| g.enemy().hp).unwrap();
+        assert_eq!(enemy_hp, Game::with_seed(1).enemy().hp);
+    }
+}
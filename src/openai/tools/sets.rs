@@ -0,0 +1,81 @@
+//! Named tool sets so callers outside Rust code (CLI flags, config) can pick tools by a short name.
+
+use color_eyre::{eyre::eyre, Result};
+
+use crate::config::{NUMBER_GUESS_MAX, NUMBER_GUESS_TARGET, X, Y};
+use super::{
+    ToolDefinition,
+    build_add_tool,
+    build_get_constants_tool,
+    build_number_guess_tool,
+    build_read_doc_tool,
+    build_rpg_tools_with,
+    build_tavily_search_tool,
+    RpgSessions,
+};
+
+/// Short names accepted by [`build_tool_set`].
+pub const TOOL_SET_NAMES: &[&str] = &["docs", "web", "math", "guess", "rpg"];
+
+/// Build the tools registered under a single set name.
+/// - `docs`  => `read_docs_file`
+/// - `web`   => `tavily_search`
+/// - `math`  => `get_constants`, `add`
+/// - `guess` => `number_guess` (`NUMBER_GUESS_TARGET` out of 1..=`NUMBER_GUESS_MAX`)
+/// - `rpg`   => all `rpg_*` tools (a fresh, isolated game store per call)
+pub fn build_tool_set(name: &str) -> Result<Vec<ToolDefinition>> {
+    build_tool_set_with_rpg(name, &RpgSessions::new())
+}
+
+fn build_tool_set_with_rpg(name: &str, rpg: &RpgSessions) -> Result<Vec<ToolDefinition>> {
+    let tools = match name.trim() {
+        "docs" => vec![build_read_doc_tool()],
+        "web" => vec![build_tavily_search_tool()],
+        "math" => vec![build_get_constants_tool(X, Y), build_add_tool()],
+        "guess" => vec![build_number_guess_tool(NUMBER_GUESS_TARGET, NUMBER_GUESS_MAX)],
+        "rpg" => build_rpg_tools_with(rpg),
+        other => {
+            return Err(eyre!(
+                "unknown tool set '{other}' (available: {})",
+                TOOL_SET_NAMES.join(", ")
+            ))
+        }
+    };
+    Ok(tools)
+}
+
+/// Build the union of several tool sets, skipping tools that were already added by an earlier set.
+pub fn build_tool_sets<S: AsRef<str>>(names: &[S]) -> Result<Vec<ToolDefinition>> {
+    build_tool_sets_with_rpg(names, &RpgSessions::new())
+}
+
+/// Like [`build_tool_sets`], but the `rpg` tools play in `rpg`, so games survive rebuilding the tools.
+pub fn build_tool_sets_with_rpg<S: AsRef<str>>(names: &[S], rpg: &RpgSessions) -> Result<Vec<ToolDefinition>> {
+    let mut tools: Vec<ToolDefinition> = Vec::new();
+    for name in names {
+        for tool in build_tool_set_with_rpg(name.as_ref(), rpg)? {
+            if !tools.iter().any(|t| t.name == tool.name) {
+                tools.push(tool);
+            }
+        }
+    }
+    Ok(tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_sets_resolve() {
+        let tools = build_tool_sets(&["docs", "math", "docs"]).unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["read_docs_file", "get_constants", "add"]);
+    }
+
+    #[test]
+    fn unknown_set_is_error() {
+        let err = build_tool_set("nope").unwrap_err().to_string();
+        assert!(err.contains("unknown tool set"));
+    }
+}
@@ -12,7 +12,7 @@ pub fn tavily_search(query: &str, max_results: u64) -> Result<String> {
     if query.trim().is_empty() { return Err(color_eyre::eyre::eyre!("query is empty")); }
     let api_key = std::env::var("tavily_API_KEY")
         .map_err(|_| color_eyre::eyre::eyre!("tavily_API_KEY not set"))?;
-    let max_results = max_results.min(10).max(1);
+    let max_results = max_results.clamp(1, 10);
     let body = json!({
         "query": query.trim(),
         "max_results": max_results,
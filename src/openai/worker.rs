@@ -3,48 +3,158 @@
 //! 以前は旧 function calling API (`functions` フィールド) を直接扱っていたが、
 //! 現在は `call_tool` モジュールの `propose_tool_call` と `tool` モジュールの
 //! `ToolDefinition` を用いて 2 ステップ (提案→実行→最終回答) を実装する。
+//!
+//! リクエストはセッション ID 付きで受け取り、セッションごとに会話履歴と設定を持つ。
+//! 別セッションのリクエストは並行して処理し、同じセッション内では順番に処理する。
 
 use crate::config::{Config};
-use crate::openai::MultiStepAnswer;
-use std::sync::mpsc::{Receiver, Sender};
+use crate::mcp::start_mcp_tools;
+use crate::openai::{ConversationHistory, MultiStepLogEvent};
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Instant;
 use tokio::runtime::Runtime;
-use tracing::{info};
-use super::call::{multi_step_tool_answer_with_logger};
-use super::tools::{
-    build_number_guess_tool,
-};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use super::call::{multi_step_tool_answer_with_history_and_logger};
+use super::tools::{build_tool_sets_with_rpg, RpgSessions};
+
+/// TUI のセッション（タブ）ID
+pub type SessionId = u64;
+
+/// ワーカーから App へ送るメッセージ
+#[derive(Debug, Clone)]
+pub enum WorkerMessage {
+    /// 多段推論の途中経過（発生時刻付き。ツール実行時間の計測に使う）
+    Event { session: SessionId, at: Instant, event: MultiStepLogEvent },
+    /// 最終回答
+    Answer { session: SessionId, answer: String },
+    /// 問い合わせの失敗（エラーの内容。会話履歴には残さない）
+    Failed { session: SessionId, error: String },
+}
+
+/// ワーカーが既定で読み込むツールセット
+pub const DEFAULT_WORKER_TOOL_SETS: &[&str] = &["guess"];
+/// ワーカーの既定の最大ループ回数
+pub const DEFAULT_WORKER_MAX_LOOPS: usize = 10;
+
+/// 以降のリクエストに適用されるワーカー設定（TUI のスラッシュコマンドで変更）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerSettings {
+    pub model: String,
+    pub max_tokens: u32,
+    /// `build_tool_sets` に渡すツールセット名
+    pub tool_sets: Vec<String>,
+    pub max_loops: usize,
+    pub system_prompt: String,
+}
+
+impl WorkerSettings {
+    /// この設定を `config` に反映（モデル・トークン数・システムプロンプト）
+    pub fn apply_to(&self, config: &mut Config) {
+        config.model = self.model.clone();
+        config.max_tokens = self.max_tokens;
+        config.system_prompt = self.system_prompt.clone();
+    }
+
+    /// `Config` の値とワーカーの既定値から作成
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            model: config.model.clone(),
+            max_tokens: config.max_tokens,
+            tool_sets: DEFAULT_WORKER_TOOL_SETS.iter().map(|s| s.to_string()).collect(),
+            max_loops: DEFAULT_WORKER_MAX_LOOPS,
+            system_prompt: config.system_prompt.clone(),
+        }
+    }
+}
+
+/// App からワーカーへ送るリクエスト
+#[derive(Debug, Clone)]
+pub enum WorkerRequest {
+    /// プロンプトを処理して回答を返す（セッションの会話履歴に続けて追加）
+    Prompt { session: SessionId, prompt: String },
+    /// セッションの以降のプロンプトに使う設定を置き換える
+    UpdateSettings { session: SessionId, settings: WorkerSettings },
+    /// セッションを閉じて会話履歴を破棄する
+    CloseSession { session: SessionId },
+}
 
 /// OpenAI APIワーカーを開始
 pub fn start_openai_worker(
-    rx_prompt: Receiver<String>,
-    tx_answer: Sender<String>,
+    rx_request: Receiver<WorkerRequest>,
+    tx_answer: UnboundedSender<WorkerMessage>,
     config: Config,
 ) {
     std::thread::spawn(move || {
-        // 専用スレッド内でTokioランタイムを構築
+        // 専用スレッド内でTokioランタイムを構築（リクエストはランタイム上のタスクとして並行実行）
         let rt = Runtime::new().expect("tokio runtime");
-        rt.block_on(async move {
-            while let Ok(prompt) = rx_prompt.recv() {
-                info!(target: "openai", "prompt_received: {}", prompt);
+        // 外部 MCP サーバーはワーカー開始時に一度だけ起動し、以降のプロンプトで共有する
+        let mcp_tools = Arc::new(start_mcp_tools(&config.mcp_servers));
+        let mut settings: HashMap<SessionId, WorkerSettings> = HashMap::new();
+        // 同じセッションのリクエストは履歴のロックで直列化される
+        let mut histories: HashMap<SessionId, Arc<Mutex<ConversationHistory>>> = HashMap::new();
+        // RPG のゲームもセッションごと（ツールはリクエストごとに作り直すが、ゲームは続く）
+        let mut rpg_games: HashMap<SessionId, RpgSessions> = HashMap::new();
 
-                let tools = vec![
-                    build_number_guess_tool(8, 10),
-                ];
+        while let Ok(request) = rx_request.recv() {
+            let (session, prompt) = match request {
+                WorkerRequest::Prompt { session, prompt } => (session, prompt),
+                WorkerRequest::UpdateSettings { session, settings: new_settings } => {
+                    info!(target: "openai", session, ?new_settings, "worker_settings_updated");
+                    settings.insert(session, new_settings);
+                    continue;
+                }
+                WorkerRequest::CloseSession { session } => {
+                    info!(target: "openai", session, "worker_session_closed");
+                    settings.remove(&session);
+                    histories.remove(&session);
+                    rpg_games.remove(&session);
+                    continue;
+                }
+            };
+            info!(target: "openai", session, "prompt_received: {}", prompt);
 
-                // Use the user's prompt as-is; log multi-step events.
-                let answer = multi_step_tool_answer_with_logger(&prompt, &tools, &config, Some(10), |ev| {
+            let session_settings = settings.entry(session).or_insert_with(|| WorkerSettings::from_config(&config)).clone();
+            let history = histories.entry(session).or_default().clone();
+            let rpg = rpg_games.entry(session).or_default().clone();
+            let mut session_config = config.clone();
+            session_settings.apply_to(&mut session_config);
+            let mcp_tools = mcp_tools.clone();
+            let tx_answer = tx_answer.clone();
+
+            rt.spawn(async move {
+                let mut tools = build_tool_sets_with_rpg(&session_settings.tool_sets, &rpg).unwrap_or_else(|e| {
+                    warn!(target: "openai", error = %e, "worker_tool_sets_invalid");
+                    Vec::new()
+                });
+                tools.extend(mcp_tools.iter().cloned());
+
+                let mut history = history.lock().await;
+                let len_before = history.len();
+                history.add_user(&prompt);
+                // Log multi-step events and forward them to the session's inspector panel.
+                let tx_event = tx_answer.clone();
+                let result = multi_step_tool_answer_with_history_and_logger(&mut history, &tools, &session_config, Some(session_settings.max_loops), |ev| {
                     tracing::info!(target="live_test", event=%ev, "multi_step_event");
-                }).await
-                .unwrap_or_else(|_| MultiStepAnswer {
-                    iterations: 0,
-                    final_answer: "エラーが発生しました。".to_string(),
-                    steps: vec![],
-                    truncated: false,
-                }).final_answer;
-                info!(target: "openai", "answer_ready: {}", answer);
-                let _ = tx_answer.send(answer);
-            }
-        });
+                    let _ = tx_event.send(WorkerMessage::Event { session, at: Instant::now(), event: ev.clone() });
+                }).await;
+                match result {
+                    Ok(answer) => {
+                        history.add_assistant(&answer.final_answer);
+                        info!(target: "openai", session, "answer_ready: {}", answer.final_answer);
+                        let _ = tx_answer.send(WorkerMessage::Answer { session, answer: answer.final_answer });
+                    }
+                    Err(e) => {
+                        // 失敗したターン（プロンプトと途中のツール結果）は履歴から外し、次の送信に持ち越さない
+                        history.truncate(len_before);
+                        warn!(target: "openai", session, error = %e, "answer_failed");
+                        let _ = tx_answer.send(WorkerMessage::Failed { session, error: format!("{e:#}") });
+                    }
+                }
+            });
+        }
     });
 }
-
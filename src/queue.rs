@@ -0,0 +1,141 @@
+//! 処理待ちプロンプトのキュー
+//!
+//! 問い合わせ中に送信されたプロンプトを保持し、回答が届くたびに先頭から1件ずつワーカーへ送る。
+//! 実行前のプロンプトは選択して入力欄に戻す（編集）か削除できる。
+
+/// 処理待ちプロンプトのキュー
+#[derive(Debug, Clone, Default)]
+pub struct PromptQueue {
+    items: Vec<String>,
+    /// 選択中の項目（編集・削除の対象）
+    selected: Option<usize>,
+}
+
+impl PromptQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 待機中のプロンプト（実行順）
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// 末尾に追加
+    pub fn push(&mut self, prompt: String) {
+        self.items.push(prompt);
+    }
+
+    /// `index` の位置に追加（編集後に元の順番へ戻す用）
+    pub fn insert(&mut self, index: usize, prompt: String) {
+        self.items.insert(index.min(self.items.len()), prompt);
+    }
+
+    /// 次に実行するプロンプトを取り出す
+    pub fn pop_front(&mut self) -> Option<String> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let prompt = self.items.remove(0);
+        self.selected = match self.selected {
+            Some(0) | None => None,
+            Some(i) => Some(i - 1),
+        };
+        if self.items.is_empty() {
+            self.selected = None;
+        }
+        Some(prompt)
+    }
+
+    /// `index` の項目を選択（範囲外なら何もしない）
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = Some(index);
+        }
+    }
+
+    /// 1つ前（先に実行される方）を選択。未選択なら末尾を選択
+    pub fn select_prev(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.selected = Some(match self.selected {
+            Some(i) => i.saturating_sub(1),
+            None => self.items.len() - 1,
+        });
+    }
+
+    /// 1つ後を選択。末尾を越えると選択解除
+    pub fn select_next(&mut self) {
+        self.selected = match self.selected {
+            Some(i) if i + 1 < self.items.len() => Some(i + 1),
+            _ => None,
+        };
+    }
+
+    /// 選択中の項目を取り出す（編集・削除用）。位置も返す
+    pub fn take_selected(&mut self) -> Option<(usize, String)> {
+        let index = self.selected?;
+        let prompt = self.items.remove(index);
+        self.selected = if self.items.is_empty() { None } else { Some(index.min(self.items.len() - 1)) };
+        Some((index, prompt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(items: &[&str]) -> PromptQueue {
+        let mut q = PromptQueue::new();
+        for item in items {
+            q.push(item.to_string());
+        }
+        q
+    }
+
+    #[test]
+    fn fifo_order() {
+        let mut q = queue(&["a", "b"]);
+        assert_eq!(q.pop_front().as_deref(), Some("a"));
+        assert_eq!(q.pop_front().as_deref(), Some("b"));
+        assert_eq!(q.pop_front(), None);
+    }
+
+    #[test]
+    fn select_and_take() {
+        let mut q = queue(&["a", "b", "c"]);
+        q.select_prev();
+        assert_eq!(q.selected(), Some(2));
+        q.select_prev();
+        assert_eq!(q.take_selected(), Some((1, "b".to_string())));
+        assert_eq!(q.items(), ["a", "c"]);
+        assert_eq!(q.selected(), Some(1));
+        q.insert(1, "b2".into());
+        assert_eq!(q.items(), ["a", "b2", "c"]);
+        q.select_next();
+        q.select_next();
+        assert_eq!(q.selected(), None);
+    }
+
+    #[test]
+    fn selection_follows_pop() {
+        let mut q = queue(&["a", "b", "c"]);
+        q.select_prev(); // c
+        q.pop_front();
+        assert_eq!(q.selected(), Some(1));
+        assert_eq!(q.items()[1], "c");
+    }
+}
@@ -1,4 +1,5 @@
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 use serde::Serialize;
 
 use super::models::*;
@@ -14,6 +15,8 @@ pub struct GameSnapshot {
     pub battle_count: usize,
     pub rules: RpgRules,
     pub is_over: bool,
+    /// Seed the game was started with; `Game::with_seed` plus the same commands replays it
+    pub seed: u64,
 }
 
 pub struct Game {
@@ -22,31 +25,63 @@ pub struct Game {
     enemy: Enemy,
     turn: Turn,
     battle_count: usize,
+    seed: u64,
+    /// Every random roll (enemies, damage, heals, escapes, drops) goes through this
+    rng: StdRng,
+}
+
+/// Player name used unless one is given (`Game::new` reads `RPG_PLAYER` instead)
+pub const DEFAULT_PLAYER_NAME: &str = "Hero";
+
+/// Player name from `RPG_PLAYER`, or `DEFAULT_PLAYER_NAME` when unset
+pub fn player_name_from_env() -> String {
+    std::env::var("RPG_PLAYER").unwrap_or_else(|_| DEFAULT_PLAYER_NAME.into())
 }
 
 impl Game {
+    /// Default rules, a random seed and the player name from `RPG_PLAYER`
     pub fn new() -> Self {
-        let rules = RpgRules::default();
-        Self::with_rules(rules)
+        Self::with_rules(RpgRules::default()).with_player_name(player_name_from_env())
     }
 
+    /// Custom rules with a random seed
     pub fn with_rules(rules: RpgRules) -> Self {
-        let name = std::env::var("RPG_PLAYER").unwrap_or_else(|_| "Hero".into());
+        Self::with_rules_and_seed(rules, thread_rng().r#gen())
+    }
+
+    /// Default rules with a fixed seed: the same commands always give the same game
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rules_and_seed(RpgRules::default(), seed)
+    }
+
+    /// Custom rules with a fixed seed. The player is `DEFAULT_PLAYER_NAME`, so the game does not
+    /// depend on the environment; use `with_player_name` to rename.
+    pub fn with_rules_and_seed(rules: RpgRules, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
         Self {
-            player: Player::new_with_rules(name, &rules),
-            enemy: Enemy::random_with_rules(&rules),
-            turn: if thread_rng().gen_bool(0.5) { Turn::Player } else { Turn::Enemy },
+            player: Player::new_with_rules(DEFAULT_PLAYER_NAME.into(), &rules),
+            enemy: Enemy::random_with_rules(&rules, &mut rng),
+            turn: if rng.gen_bool(0.5) { Turn::Player } else { Turn::Enemy },
             battle_count: 1,
+            seed,
+            rng,
             rules,
         }
     }
 
+    /// Rename the player (does not touch the RNG, so replays stay identical)
+    pub fn with_player_name(mut self, name: impl Into<String>) -> Self {
+        self.player.name = name.into();
+        self
+    }
+
     pub fn is_over(&self) -> bool { self.player.hp <= 0 }
 
     pub fn player(&self) -> &Player { &self.player }
     pub fn enemy(&self) -> &Enemy { &self.enemy }
     pub fn battle_count(&self) -> usize { self.battle_count }
     pub fn turn(&self) -> Turn { self.turn }
+    pub fn seed(&self) -> u64 { self.seed }
 
     pub fn snapshot(&self) -> GameSnapshot {
         GameSnapshot {
@@ -56,32 +91,39 @@ impl Game {
             battle_count: self.battle_count,
             rules: self.rules.clone(),
             is_over: self.is_over(),
+            seed: self.seed,
         }
     }
 
-    pub fn handle_command(&mut self, cmd: Command) -> Result<bool> {
+    /// Apply a player command and return what happened, in order.
+    /// The game never prints; frontends render the events themselves.
+    pub fn handle_command(&mut self, cmd: Command) -> Vec<GameEvent> {
+        let mut events = Vec::new();
         match cmd {
-            Command::Quit => return Ok(false),
+            Command::Quit => {
+                events.push(GameEvent::Quit);
+                return events;
+            }
             Command::Run => {
-                if thread_rng().gen_bool(self.rules.run_success_rate) {
-                    println!("You ran away!");
-                    self.next_battle();
+                if self.rng.gen_bool(self.rules.run_success_rate) {
+                    events.push(GameEvent::Fled);
+                    self.next_battle(&mut events);
                 } else {
-                    println!("Couldn't escape!");
+                    events.push(GameEvent::FleeFailed);
                     self.turn = Turn::Enemy;
                 }
             }
             Command::Heal => {
-                let healed = self.player.heal(&self.rules);
-                if healed > 0 { println!("You used a potion and healed {healed} HP."); } else { println!("No potions left!"); }
+                let healed = self.player.heal(&self.rules, &mut self.rng);
+                events.push(if healed > 0 { GameEvent::Healed { amount: healed } } else { GameEvent::NoPotions });
                 self.turn = Turn::Enemy;
             }
             Command::Attack => {
-                let dmg = player_attack_damage(self.player.atk);
-                println!("You hit the {} for {dmg} damage!", self.enemy.name);
+                let dmg = player_attack_damage(self.player.atk, &mut self.rng);
                 self.enemy.hp -= dmg;
+                events.push(GameEvent::DamageDealt { target: self.enemy.name.clone(), damage: dmg });
                 if self.enemy.hp <= 0 {
-                    self.victory();
+                    self.victory(&mut events);
                 } else {
                     self.turn = Turn::Enemy;
                 }
@@ -89,37 +131,76 @@ impl Game {
         }
 
         if self.turn == Turn::Enemy && self.player.hp > 0 {
-            self.enemy_turn();
+            self.enemy_turn(&mut events);
         }
-        Ok(true)
+        events
     }
 
-    fn enemy_turn(&mut self) {
-        let dmg = enemy_attack_damage(self.enemy.atk);
-        println!("{} hits you for {dmg} damage!", self.enemy.name);
+    fn enemy_turn(&mut self, events: &mut Vec<GameEvent>) {
+        let dmg = enemy_attack_damage(self.enemy.atk, &mut self.rng);
         self.player.hp -= dmg;
+        events.push(GameEvent::DamageTaken { attacker: self.enemy.name.clone(), damage: dmg });
         if self.player.hp <= 0 {
-            println!("You were defeated... Game Over.");
+            events.push(GameEvent::Defeat);
         } else {
             self.turn = Turn::Player;
         }
     }
 
-    fn victory(&mut self) {
-        println!("You defeated the {}!", self.enemy.name);
-        println!("You found {} gold.", self.enemy.gold_reward);
+    fn victory(&mut self, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::EnemyDefeated { name: self.enemy.name.clone() });
         self.player.gold += self.enemy.gold_reward;
-        if thread_rng().gen_bool(self.rules.potion_drop_rate) {
+        let potion = self.rng.gen_bool(self.rules.potion_drop_rate);
+        if potion {
             self.player.potions += 1;
-            println!("You found a potion!");
         }
-        self.next_battle();
+        events.push(GameEvent::Loot { gold: self.enemy.gold_reward, potion });
+        self.next_battle(events);
     }
 
-    fn next_battle(&mut self) {
-        self.enemy = Enemy::random_with_rules(&self.rules);
+    fn next_battle(&mut self, events: &mut Vec<GameEvent>) {
+        self.enemy = Enemy::random_with_rules(&self.rules, &mut self.rng);
         self.turn = Turn::Player;
         self.battle_count += 1;
-        println!("A wild {} appears!", self.enemy.name);
+        events.push(GameEvent::EnemyAppeared { name: self.enemy.name.clone(), hp: self.enemy.hp });
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attack_reports_damage_that_matches_the_state() {
+        let mut game = Game::with_seed(7);
+        let before = game.enemy().hp;
+        let events = game.handle_command(Command::Attack);
+        // No enemy template dies to a single hit, so the enemy always strikes back
+        let GameEvent::DamageDealt { damage, .. } = &events[0] else { panic!("{events:?}") };
+        assert_eq!(game.enemy().hp, before - damage);
+        let GameEvent::DamageTaken { damage, .. } = &events[1] else { panic!("{events:?}") };
+        assert_eq!(game.player().hp, game.player().max_hp - damage);
+    }
+
+    #[test]
+    fn heal_and_quit_events() {
+        let mut game = Game::with_seed(7);
+        let potions = game.player().potions;
+        assert!(matches!(game.handle_command(Command::Heal)[0], GameEvent::Healed { .. }));
+        assert_eq!(game.player().potions, potions - 1);
+        assert_eq!(game.handle_command(Command::Quit), [GameEvent::Quit]);
+    }
+
+    #[test]
+    fn events_serialize_with_a_type_tag() {
+        let event = GameEvent::Loot { gold: 6, potion: true };
+        assert_eq!(serde_json::to_value(&event).unwrap(), serde_json::json!({"type": "loot", "gold": 6, "potion": true}));
+        assert_eq!(event.to_string(), "You found 6 gold and a potion!");
     }
 }
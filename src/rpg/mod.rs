@@ -2,7 +2,8 @@ pub mod rules;
 pub mod models;
 pub mod game;
 pub mod ui;
+pub mod tui;
 
-pub use game::Game;
-pub use models::{Player, Enemy, Turn, Command};
+pub use game::{player_name_from_env, Game, DEFAULT_PLAYER_NAME};
+pub use models::{Player, Enemy, Turn, Command, GameEvent};
 pub use rules::{RpgRules, EnemyTemplate};
@@ -1,4 +1,6 @@
-use rand::{thread_rng, Rng};
+use std::fmt;
+
+use rand::Rng;
 use serde::{Serialize, Deserialize};
 
 use super::rules::RpgRules;
@@ -24,10 +26,10 @@ impl Player {
             gold: rules.player_default_gold,
         }
     }
-    pub fn heal(&mut self, rules: &RpgRules) -> i32 {
+    pub fn heal(&mut self, rules: &RpgRules, rng: &mut impl Rng) -> i32 {
         if self.potions <= 0 { return 0; }
         self.potions -= 1;
-        let heal = thread_rng().gen_range(rules.heal_min..=rules.heal_max);
+        let heal = rng.gen_range(rules.heal_min..=rules.heal_max);
         self.hp = (self.hp + heal).min(self.max_hp);
         heal
     }
@@ -37,16 +39,16 @@ impl Player {
 pub struct Enemy {
     pub name: String,
     pub hp: i32,
+    pub max_hp: i32,
     pub atk: i32,
     pub gold_reward: i32,
 }
 
 impl Enemy {
-    pub fn random_with_rules(rules: &RpgRules) -> Self {
-        let mut rng = thread_rng();
+    pub fn random_with_rules(rules: &RpgRules, rng: &mut impl Rng) -> Self {
         let idx = rng.gen_range(0..rules.enemy_templates.len());
         let t = &rules.enemy_templates[idx];
-        Self { name: t.name.clone(), hp: t.hp, atk: t.atk, gold_reward: t.gold_reward }
+        Self { name: t.name.clone(), hp: t.hp, max_hp: t.hp, atk: t.atk, gold_reward: t.gold_reward }
     }
 }
 
@@ -56,14 +58,60 @@ pub enum Turn { Player, Enemy }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Command { Attack, Heal, Run, Quit }
 
-pub fn player_attack_damage(atk: i32) -> i32 {
-    let mut rng = thread_rng();
+/// Something that happened while handling a command (returned by `Game::handle_command`).
+/// `Display` gives the classic one-line message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The player hit the enemy
+    DamageDealt { target: String, damage: i32 },
+    /// The enemy hit the player
+    DamageTaken { attacker: String, damage: i32 },
+    /// A potion restored `amount` HP (before capping at max HP)
+    Healed { amount: i32 },
+    /// Heal was chosen without potions
+    NoPotions,
+    /// Running away worked; a new battle follows
+    Fled,
+    /// Running away failed; the enemy acts
+    FleeFailed,
+    /// The enemy was defeated
+    EnemyDefeated { name: String },
+    /// Rewards for the victory
+    Loot { gold: i32, potion: bool },
+    /// A new enemy entered the battle
+    EnemyAppeared { name: String, hp: i32 },
+    /// The player's HP reached zero
+    Defeat,
+    /// The player quit
+    Quit,
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::DamageDealt { target, damage } => write!(f, "You hit the {target} for {damage} damage!"),
+            GameEvent::DamageTaken { attacker, damage } => write!(f, "{attacker} hits you for {damage} damage!"),
+            GameEvent::Healed { amount } => write!(f, "You used a potion and healed {amount} HP."),
+            GameEvent::NoPotions => f.write_str("No potions left!"),
+            GameEvent::Fled => f.write_str("You ran away!"),
+            GameEvent::FleeFailed => f.write_str("Couldn't escape!"),
+            GameEvent::EnemyDefeated { name } => write!(f, "You defeated the {name}!"),
+            GameEvent::Loot { gold, potion: true } => write!(f, "You found {gold} gold and a potion!"),
+            GameEvent::Loot { gold, potion: false } => write!(f, "You found {gold} gold."),
+            GameEvent::EnemyAppeared { name, .. } => write!(f, "A wild {name} appears!"),
+            GameEvent::Defeat => f.write_str("You were defeated... Game Over."),
+            GameEvent::Quit => f.write_str("You left the battle."),
+        }
+    }
+}
+
+pub fn player_attack_damage(atk: i32, rng: &mut impl Rng) -> i32 {
     let variance = rng.gen_range(-1..=2);
     (atk + variance).max(1)
 }
 
-pub fn enemy_attack_damage(atk: i32) -> i32 {
-    let mut rng = thread_rng();
+pub fn enemy_attack_damage(atk: i32, rng: &mut impl Rng) -> i32 {
     let variance = rng.gen_range(-1..=1);
     (atk + variance).max(1)
 }
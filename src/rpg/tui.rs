@@ -0,0 +1,206 @@
+//! ratatui front-end for the Tiny RPG: HP bars, a scrolling battle log,
+//! an inventory panel and single-key actions (run it with `cargo run --example rpg_tui`).
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::{DefaultTerminal, Frame};
+
+use super::game::Game;
+use super::models::{Command, GameEvent};
+use crate::theme::Theme;
+
+/// Width of the HP bars in cells
+const HP_BAR_WIDTH: usize = 20;
+/// Lines moved by PgUp / PgDn in the battle log
+const LOG_PAGE: usize = 5;
+
+/// Screen state: the game plus everything that happened in it so far
+pub struct RpgTui {
+    pub game: Game,
+    /// Battle log (oldest first)
+    pub log: Vec<GameEvent>,
+    pub theme: Theme,
+    /// Lines scrolled up from the bottom of the log (0 follows new messages)
+    log_scroll: usize,
+    /// Largest useful `log_scroll`, known after rendering
+    max_log_scroll: usize,
+}
+
+impl RpgTui {
+    pub fn new(game: Game, theme: Theme) -> Self {
+        let log = vec![Self::intro(&game)];
+        Self { game, log, theme, log_scroll: 0, max_log_scroll: 0 }
+    }
+
+    /// The first enemy is not announced by any command, so the screen announces it
+    fn intro(game: &Game) -> GameEvent {
+        GameEvent::EnemyAppeared { name: game.enemy().name.clone(), hp: game.enemy().hp }
+    }
+
+    /// Apply a key press. Returns `true` when the player quits.
+    ///
+    /// A/H/R act while the battle is on, N starts a new game after a defeat,
+    /// PgUp/PgDn scroll the log and F5 cycles the theme.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return key.code == KeyCode::Char('c');
+        }
+        let over = self.game.is_over();
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Char(c) => match (c.to_ascii_lowercase(), over) {
+                ('q', _) => return true,
+                ('a', false) => self.act(Command::Attack),
+                ('h', false) => self.act(Command::Heal),
+                ('r', false) => self.act(Command::Run),
+                ('n', true) => {
+                    let name = self.game.player().name.clone();
+                    self.game = Game::with_rules(self.game.rules.clone()).with_player_name(name);
+                    self.log = vec![Self::intro(&self.game)];
+                    self.log_scroll = 0;
+                }
+                _ => {}
+            },
+            KeyCode::PageUp => self.log_scroll = (self.log_scroll + LOG_PAGE).min(self.max_log_scroll),
+            KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(LOG_PAGE),
+            KeyCode::F(5) => self.theme = self.theme.next(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Run a command, append its events and jump back to the newest line
+    fn act(&mut self, command: Command) {
+        self.log.extend(self.game.handle_command(command));
+        self.log_scroll = 0;
+    }
+
+    pub fn render(&mut self, f: &mut Frame) {
+        let theme = self.theme;
+        let [status, main, footer] =
+            Layout::vertical([Constraint::Length(4), Constraint::Min(3), Constraint::Length(1)]).areas(f.area());
+        let [log_area, inventory] = Layout::horizontal([Constraint::Min(20), Constraint::Length(24)]).areas(main);
+
+        let player = self.game.player();
+        let enemy = self.game.enemy();
+        let name_width = player.name.chars().count().max(enemy.name.chars().count());
+        let fighter = |name: &str, hp: i32, max_hp: i32, style| {
+            Line::from(vec![
+                Span::styled(format!("{name:<name_width$} "), style),
+                Span::styled(hp_bar(hp, max_hp, HP_BAR_WIDTH), style),
+                Span::raw(format!(" {:>3}/{max_hp}", hp.max(0))),
+            ])
+        };
+        let title = format!("{} - Battle #{}", self.game.rules.game_name, self.game.battle_count());
+        f.render_widget(
+            Paragraph::new(vec![
+                fighter(&player.name, player.hp, player.max_hp, theme.user),
+                fighter(&enemy.name, enemy.hp, enemy.max_hp, theme.error),
+            ])
+            .block(theme.block(title)),
+            status,
+        );
+
+        // Keep the newest line at the bottom; PgUp walks back from there
+        let height = theme.block("").inner(log_area).height as usize;
+        self.max_log_scroll = self.log.len().saturating_sub(height);
+        self.log_scroll = self.log_scroll.min(self.max_log_scroll);
+        let top = self.max_log_scroll - self.log_scroll;
+        let lines: Vec<Line> = self.log.iter().skip(top).take(height).map(|e| Line::styled(e.to_string(), event_style(e, &theme))).collect();
+        let log_title = if self.log_scroll > 0 { format!("Battle Log (+{} newer)", self.log_scroll) } else { "Battle Log".into() };
+        f.render_widget(Paragraph::new(lines).block(theme.block(log_title)), log_area);
+
+        let items = vec![
+            Line::from(format!("Potions: {}", player.potions)),
+            Line::from(format!("Gold:    {}", player.gold)),
+            Line::from(format!("ATK:     {}", player.atk)),
+            Line::styled(format!("Enemy ATK: {}", enemy.atk), theme.muted),
+            Line::styled(format!("Turn: {:?}", self.game.turn()), theme.muted),
+            Line::styled(format!("Seed: {}", self.game.seed()), theme.muted),
+        ];
+        f.render_widget(Paragraph::new(items).block(theme.block("Inventory")), inventory);
+
+        let help = if self.game.is_over() {
+            Line::styled("Game over - [N]ew game  [Q]uit", theme.error)
+        } else {
+            Line::styled(format!("[A]ttack  [H]eal  [R]un  [Q]uit  PgUp/PgDn log  F5 theme ({})", theme.name), theme.muted)
+        };
+        f.render_widget(Paragraph::new(help), footer);
+    }
+}
+
+/// Log color for an event: hits on the player and defeats stand out, rewards use the tool color
+fn event_style(event: &GameEvent, theme: &Theme) -> Style {
+    match event {
+        GameEvent::DamageTaken { .. } | GameEvent::Defeat => theme.error,
+        GameEvent::Healed { .. } | GameEvent::EnemyDefeated { .. } | GameEvent::Loot { .. } => theme.tool,
+        GameEvent::EnemyAppeared { .. } => theme.title,
+        GameEvent::NoPotions | GameEvent::FleeFailed => theme.warning,
+        _ => theme.assistant,
+    }
+}
+
+/// Text HP bar such as `██████░░░░` (readable without colors).
+pub fn hp_bar(hp: i32, max_hp: i32, width: usize) -> String {
+    let filled = if max_hp <= 0 { 0 } else { (hp.clamp(0, max_hp) as usize * width).div_ceil(max_hp as usize) };
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Draw and handle keys until the player quits. Redraws only after input.
+pub fn run(terminal: &mut DefaultTerminal, tui: &mut RpgTui) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|f| tui.render(f))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && tui.handle_key(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::buffer_to_string;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn hp_bar_rounds_up_partial_health() {
+        assert_eq!(hp_bar(30, 30, 10), "█".repeat(10));
+        assert_eq!(hp_bar(1, 30, 10), format!("█{}", "░".repeat(9)));
+        assert_eq!(hp_bar(-3, 30, 4), "░░░░");
+    }
+
+    #[test]
+    fn keys_drive_the_game_and_fill_the_log() {
+        let mut tui = RpgTui::new(Game::with_seed(7), Theme::default());
+        assert!(matches!(tui.log[0], GameEvent::EnemyAppeared { .. }));
+        assert!(!tui.handle_key(key('n'))); // no new game while the battle is on
+        assert_eq!(tui.log.len(), 1);
+
+        assert!(!tui.handle_key(key('A')));
+        assert!(matches!(tui.log[1], GameEvent::DamageDealt { .. }));
+        assert!(tui.handle_key(key('q')));
+    }
+
+    #[test]
+    fn renders_bars_log_and_inventory() {
+        let mut tui = RpgTui::new(Game::with_seed(7), Theme::default().without_color());
+        let mut terminal = Terminal::new(TestBackend::new(80, 16)).unwrap();
+        terminal.draw(|f| tui.render(f)).unwrap();
+        let screen = buffer_to_string(terminal.backend().buffer());
+        assert!(screen.contains(&format!("{}  30/30", "█".repeat(HP_BAR_WIDTH))), "{screen}");
+        assert!(screen.contains("A wild"), "{screen}");
+        assert!(screen.contains("Potions: 2"), "{screen}");
+        assert!(screen.contains("Battle #1"), "{screen}");
+    }
+}
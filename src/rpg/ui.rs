@@ -1,5 +1,5 @@
 use super::game::Game;
-use super::models::{Command};
+use super::models::{Command, GameEvent};
 use super::game::Result;
 use std::io::{self, Write};
 
@@ -22,6 +22,13 @@ pub fn show_status(game: &Game) {
     println!("Turn:   {:?}", game.turn());
 }
 
+/// Print the events returned by the last command.
+pub fn show_events(events: &[GameEvent]) {
+    for event in events {
+        println!("{event}");
+    }
+}
+
 pub fn prompt_action() -> Result<Command> {
     print!("[A]ttack, [H]eal, [R]un, [Q]uit > ");
     io::stdout().flush()?;
@@ -0,0 +1,128 @@
+//! セッション（タブ）ごとの状態
+//!
+//! 会話履歴そのものはワーカー側でセッション ID ごとに保持し、ここでは表示と送信の状態を持つ。
+
+use std::fmt::{self, Display};
+
+use crate::answer_view::AnswerView;
+use crate::inspector::ToolInspector;
+use crate::openai::{SessionId, WorkerSettings};
+use crate::queue::PromptQueue;
+
+/// 最後に送信されたテキストの初期表示
+pub const NOTHING_SUBMITTED: &str = "(まだありません)";
+
+/// タブバーに表示するセッションの状態
+///
+/// 要望にあった「承認待ち」は含まない。ツール実行の承認フローがまだないため、その状態になる経路がない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    /// 待機中
+    Idle,
+    /// 問い合わせ中（`queued` 件が待機キューにある）
+    Thinking { queued: usize },
+}
+
+impl Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionStatus::Idle => f.write_str("idle"),
+            SessionStatus::Thinking { queued: 0 } => f.write_str("thinking"),
+            SessionStatus::Thinking { queued } => write!(f, "thinking +{queued}"),
+        }
+    }
+}
+
+/// 1つのセッション（タブ）の状態
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// ワーカーへのリクエストに付ける ID
+    pub id: SessionId,
+    /// 最後に送信されたテキスト
+    pub last_submitted: String,
+    /// AI回答（受信済みの場合）
+    pub ai_answer: Option<String>,
+    /// AI回答ペインのスクロール状態
+    pub answer_view: AnswerView,
+    /// ツール呼び出しのタイムライン
+    pub inspector: ToolInspector,
+    /// このセッションでワーカーに適用中の設定（ヘッダーに表示）
+    pub settings: WorkerSettings,
+    /// 問い合わせ中に送信されたプロンプトの待機キュー
+    pub queue: PromptQueue,
+    /// 待機キューから編集中の項目の元の位置（再送信時にそこへ戻す）
+    pub editing_queued: Option<usize>,
+    /// 最後にコピーしたコードブロックの位置（次のコピーはその次から）
+    pub copied_code_block: Option<usize>,
+    /// AI処理中フラグ
+    pub pending: bool,
+}
+
+impl Session {
+    pub fn new(id: SessionId, settings: WorkerSettings) -> Self {
+        Self {
+            id,
+            last_submitted: NOTHING_SUBMITTED.to_string(),
+            ai_answer: None,
+            answer_view: AnswerView::new(),
+            inspector: ToolInspector::new(),
+            settings,
+            queue: PromptQueue::new(),
+            editing_queued: None,
+            copied_code_block: None,
+            pending: false,
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        if self.pending {
+            SessionStatus::Thinking { queued: self.queue.len() }
+        } else {
+            SessionStatus::Idle
+        }
+    }
+
+    /// タブの見出し（最後に送信したプロンプトの先頭）
+    pub fn title(&self) -> String {
+        if self.last_submitted == NOTHING_SUBMITTED {
+            return "new".to_string();
+        }
+        let first_line = self.last_submitted.lines().next().unwrap_or("");
+        let mut title: String = first_line.chars().take(12).collect();
+        if title.chars().count() < first_line.chars().count() {
+            title.push('…');
+        }
+        title
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn session() -> Session {
+        Session::new(1, WorkerSettings::from_config(&Config::new()))
+    }
+
+    #[test]
+    fn title_uses_first_line_of_last_prompt() {
+        let mut s = session();
+        assert_eq!(s.title(), "new");
+        s.last_submitted = "短い質問\n2行目".to_string();
+        assert_eq!(s.title(), "短い質問");
+        s.last_submitted = "abcdefghijklmnop".to_string();
+        assert_eq!(s.title(), "abcdefghijkl…");
+    }
+
+    #[test]
+    fn status_reports_queue_while_thinking() {
+        let mut s = session();
+        assert_eq!(s.status(), SessionStatus::Idle);
+        s.pending = true;
+        assert_eq!(s.status().to_string(), "thinking");
+        s.queue.push("next".into());
+        s.queue.push("after".into());
+        assert_eq!(s.status().to_string(), "thinking +2");
+    }
+}
@@ -0,0 +1,242 @@
+//! 配色テーマ
+//!
+//! 組み込みの dark / light / high-contrast から `Config::theme` で選び、実行中は `toggle_theme` で切り替える。
+//! 環境変数 `NO_COLOR` が設定されていれば色を付けず、太字・反転などの装飾だけで区別する。
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use color_eyre::eyre::{eyre, Report};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+
+/// 組み込みテーマの名前
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemeName {
+    /// 全テーマ（切り替えの順）
+    pub const ALL: [ThemeName; 3] = [ThemeName::Dark, ThemeName::Light, ThemeName::HighContrast];
+
+    /// 設定で使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high-contrast",
+        }
+    }
+
+    /// 切り替えで次に選ぶテーマ
+    pub fn next(&self) -> ThemeName {
+        let i = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ThemeName {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase().replace('_', "-");
+        Self::ALL
+            .into_iter()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| eyre!("unknown theme `{s}` (dark, light, high-contrast)"))
+    }
+}
+
+/// ウィジェットごとのスタイル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: ThemeName,
+    /// 色を使うか（`NO_COLOR` のときは false）
+    pub color: bool,
+    /// ペインの枠線
+    pub border: Style,
+    /// ペインのタイトル・見出し
+    pub title: Style,
+    /// ユーザーの入力（入力欄・直近送信・待機キュー）
+    pub user: Style,
+    /// AI の回答
+    pub assistant: Style,
+    /// ツール呼び出し・設定表示
+    pub tool: Style,
+    /// エラー
+    pub error: Style,
+    /// 選択中の項目
+    pub highlight: Style,
+    /// 補足・ヒント
+    pub muted: Style,
+    /// 処理中・ステータスメッセージ
+    pub warning: Style,
+    /// Markdown のコードブロック・インラインコード
+    pub code: Style,
+    /// Markdown の見出し（H1 はさらに下線、H3 以降は太字のみ）
+    pub heading: Style,
+    /// Markdown のリンク
+    pub link: Style,
+    /// Markdown のリスト記号・引用線・区切り線・コードの言語名
+    pub marker: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::default())
+    }
+}
+
+impl Theme {
+    /// 組み込みテーマ
+    pub fn new(name: ThemeName) -> Self {
+        let bold = Modifier::BOLD;
+        match name {
+            ThemeName::Dark => Self {
+                name,
+                color: true,
+                border: Style::new().fg(Color::Gray),
+                title: Style::new().fg(Color::Cyan).add_modifier(bold),
+                user: Style::new().fg(Color::LightBlue),
+                assistant: Style::new(),
+                tool: Style::new().fg(Color::Green),
+                error: Style::new().fg(Color::LightRed).add_modifier(bold),
+                highlight: Style::new().fg(Color::Black).bg(Color::Cyan),
+                muted: Style::new().fg(Color::DarkGray),
+                warning: Style::new().fg(Color::Yellow),
+                code: Style::new().fg(Color::LightYellow).bg(Color::Rgb(40, 40, 40)),
+                heading: Style::new().fg(Color::Cyan).add_modifier(bold),
+                link: Style::new().fg(Color::LightBlue).add_modifier(Modifier::UNDERLINED),
+                marker: Style::new().fg(Color::DarkGray),
+            },
+            ThemeName::Light => Self {
+                name,
+                color: true,
+                border: Style::new().fg(Color::DarkGray),
+                title: Style::new().fg(Color::Blue).add_modifier(bold),
+                user: Style::new().fg(Color::Blue),
+                assistant: Style::new().fg(Color::Black),
+                tool: Style::new().fg(Color::Rgb(0, 120, 0)),
+                error: Style::new().fg(Color::Red).add_modifier(bold),
+                highlight: Style::new().fg(Color::White).bg(Color::Blue),
+                muted: Style::new().fg(Color::Gray),
+                warning: Style::new().fg(Color::Rgb(175, 95, 0)),
+                code: Style::new().fg(Color::Rgb(120, 40, 0)).bg(Color::Rgb(235, 235, 235)),
+                heading: Style::new().fg(Color::Blue).add_modifier(bold),
+                link: Style::new().fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+                marker: Style::new().fg(Color::Gray),
+            },
+            ThemeName::HighContrast => Self {
+                name,
+                color: true,
+                border: Style::new().fg(Color::White),
+                title: Style::new().fg(Color::White).add_modifier(bold | Modifier::UNDERLINED),
+                user: Style::new().fg(Color::LightCyan).add_modifier(bold),
+                assistant: Style::new().fg(Color::White),
+                tool: Style::new().fg(Color::LightGreen).add_modifier(bold),
+                error: Style::new().fg(Color::LightRed).add_modifier(bold | Modifier::UNDERLINED),
+                highlight: Style::new().fg(Color::Black).bg(Color::Yellow).add_modifier(bold),
+                muted: Style::new().fg(Color::White),
+                warning: Style::new().fg(Color::LightYellow).add_modifier(bold),
+                code: Style::new().fg(Color::Black).bg(Color::White),
+                heading: Style::new().fg(Color::LightYellow).add_modifier(bold),
+                link: Style::new().fg(Color::LightCyan).add_modifier(bold | Modifier::UNDERLINED),
+                marker: Style::new().fg(Color::White).add_modifier(bold),
+            },
+        }
+    }
+
+    /// 組み込みテーマ（`NO_COLOR` が設定されていれば色なし）
+    pub fn from_env(name: ThemeName) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        if no_color { Self::new(name).without_color() } else { Self::new(name) }
+    }
+
+    /// 色を外したテーマ（選択は反転表示で示す）
+    pub fn without_color(self) -> Self {
+        Self {
+            color: false,
+            border: strip_color(self.border),
+            title: strip_color(self.title),
+            user: strip_color(self.user),
+            assistant: strip_color(self.assistant),
+            tool: strip_color(self.tool),
+            error: strip_color(self.error),
+            highlight: strip_color(self.highlight).add_modifier(Modifier::REVERSED),
+            muted: strip_color(self.muted),
+            warning: strip_color(self.warning),
+            // 背景色がなくなるのでコードは反転表示で区別する
+            code: strip_color(self.code).add_modifier(Modifier::REVERSED),
+            heading: strip_color(self.heading),
+            link: strip_color(self.link),
+            marker: strip_color(self.marker),
+            ..self
+        }
+    }
+
+    /// 次のテーマ（色の有無は引き継ぐ）
+    pub fn next(&self) -> Self {
+        let next = Self::new(self.name.next());
+        if self.color { next } else { next.without_color() }
+    }
+
+    /// テーマの枠線とタイトルで囲んだブロック
+    pub fn block<'a>(&self, title: impl Into<String>) -> Block<'a> {
+        Block::bordered().border_style(self.border).title(Span::styled(title.into(), self.title))
+    }
+}
+
+/// 前景・背景・下線の色を外す
+fn strip_color(style: Style) -> Style {
+    Style { fg: None, bg: None, underline_color: None, ..style }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip_and_cycle() {
+        for name in ThemeName::ALL {
+            assert_eq!(name.to_string().parse::<ThemeName>().unwrap(), name);
+        }
+        assert_eq!("High_Contrast".parse::<ThemeName>().unwrap(), ThemeName::HighContrast);
+        assert!("solarized".parse::<ThemeName>().is_err());
+        assert_eq!(ThemeName::HighContrast.next(), ThemeName::Dark);
+    }
+
+    #[test]
+    fn without_color_keeps_only_modifiers() {
+        let theme = Theme::new(ThemeName::Dark).without_color();
+        assert!(!theme.color);
+        assert_eq!(theme.border, Style::new());
+        assert_eq!(theme.error, Style::new().add_modifier(Modifier::BOLD));
+        assert!(theme.highlight.add_modifier.contains(Modifier::REVERSED));
+        // 切り替えても色なしのまま
+        let next = theme.next();
+        assert_eq!((next.name, next.color), (ThemeName::Light, false));
+    }
+
+    #[test]
+    fn markdown_styles_differ_per_theme_and_lose_color_without_it() {
+        let [dark, light, contrast] = ThemeName::ALL.map(Theme::new);
+        assert_ne!(dark.code, light.code);
+        assert_ne!(light.code, contrast.code);
+        assert_ne!(dark.link, light.link);
+        let plain = dark.without_color();
+        assert_eq!(plain.code, Style::new().add_modifier(Modifier::REVERSED));
+        assert_eq!(plain.marker, Style::new());
+        assert_eq!(plain.link, Style::new().add_modifier(Modifier::UNDERLINED));
+    }
+}
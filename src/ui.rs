@@ -1,81 +1,257 @@
 //! UI描画モジュール
 
 use crate::app::App;
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::Stylize;
+use crate::commands::format_tool_sets;
+use crate::keymap::{Action, Keymap};
+use crate::layout;
+use crate::markdown::render_markdown;
+use crate::queue::PromptQueue;
+use crate::theme::Theme;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Clear, Paragraph, Tabs};
+use ratatui::buffer::Buffer;
 use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+/// 入力欄の最大表示行数（超えた分はスクロール）
+const MAX_INPUT_LINES: usize = 5;
+/// 待機キューの最大表示件数
+const MAX_QUEUE_LINES: usize = 5;
 
 /// メインUI描画関数
-pub fn render(f: &mut Frame, app: &App) {
-    let area = f.area();
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(4),  // ヘッダ
-            Constraint::Length(3),  // 入力欄
-            Constraint::Length(3),  // 直近送信
-            Constraint::Length(30), // AI回答
-            Constraint::Min(0),     // 余白
-        ])
-        .split(area);
+///
+/// 端末サイズに合わせてペインを配置し（`layout::compute`）、配置をマウスの当たり判定用に `App::panes` に残す。
+pub fn render(f: &mut Frame, app: &mut App) {
+    // 入力欄は行数に合わせて伸ばす（最大 MAX_INPUT_LINES 行 + 枠）
+    let input_height = app.input.line_count().clamp(1, MAX_INPUT_LINES) as u16 + 2;
+    // 待機キューは空なら表示しない
+    let queue = &app.session().queue;
+    let queue_height = if queue.is_empty() { 0 } else { queue.len().min(MAX_QUEUE_LINES) as u16 + 2 };
+    let panes = layout::compute(f.area(), input_height, queue_height, app.show_inspector);
+    app.panes = panes;
+
+    render_tabs(f, app, panes.tabs);
+    if !panes.header.is_empty() {
+        render_header(f, app, panes.header);
+    }
+    render_input(f, app, panes.input);
+    if !panes.last_submitted.is_empty() {
+        render_last_submitted(f, app, panes.last_submitted);
+    }
+    if !panes.queue.is_empty() {
+        render_queue(f, app, panes.queue);
+    }
+    if !panes.answer.is_empty() {
+        render_ai_response(f, app, panes.answer);
+    }
+    if !panes.inspector.is_empty() {
+        app.session().inspector.render(f, panes.inspector, &app.theme);
+    }
+    render_footer(f, app, panes.footer);
+
+    // オーバーレイは最後に重ねて描画
+    if app.show_help {
+        render_help(f, &app.keymap, &app.theme);
+    }
+    if app.confirm_quit {
+        render_confirm_quit(f, &app.theme);
+    }
+}
+
+/// タブの見出し（番号・最後のプロンプトの先頭・状態）
+fn tab_labels(app: &App) -> Vec<String> {
+    app.sessions.iter().enumerate().map(|(i, s)| format!("{} {} [{}]", i + 1, s.title(), s.status())).collect()
+}
+
+/// タブバー上の列 `column` にあるタブの位置
+///
+/// `Tabs` は各見出しを左右1文字の余白付きで描き、区切り文字1文字を挟む。
+pub fn tab_at(app: &App, column: u16) -> Option<usize> {
+    let mut x = app.panes.tabs.x;
+    for (i, label) in tab_labels(app).iter().enumerate() {
+        let end = x + label.width() as u16 + 2;
+        if (x..end).contains(&column) {
+            return Some(i);
+        }
+        x = end + 1;
+    }
+    None
+}
 
-    render_header(f, chunks[0]);
-    render_input(f, app, chunks[1]);
-    render_last_submitted(f, app, chunks[2]);
-    render_ai_response(f, app, chunks[3]);
-    render_footer(f, app, chunks[4]);
+/// 待機キューの表示の先頭（選択中の項目が見えるようにずらす）
+pub fn queue_scroll(queue: &PromptQueue) -> usize {
+    queue.selected().map(|i| (i + 1).saturating_sub(MAX_QUEUE_LINES)).unwrap_or(0)
+}
+
+/// タブバーを描画（番号・見出し・状態）
+fn render_tabs(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
+    let titles = tab_labels(app).into_iter().zip(&app.sessions).map(|(label, s)| {
+        if s.pending { Line::styled(label, theme.warning) } else { Line::from(label) }
+    });
+    let tabs = Tabs::new(titles)
+        .select(app.active)
+        .highlight_style(theme.highlight)
+        .divider(Span::styled("|", theme.border));
+    f.render_widget(tabs, area);
 }
 
 /// ヘッダー/ガイド部分を描画
-fn render_header(f: &mut Frame, area: ratatui::layout::Rect) {
+fn render_header(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let keymap = &app.keymap;
+    let theme = &app.theme;
+    let s = &app.session().settings;
+    let system = if s.system_prompt == crate::config::DEFAULT_SYSTEM_PROMPT { "default" } else { "custom" };
     let guide = vec![
-        Line::from("Ratatui ECHO デモ".bold()),
-        Line::from("文字をタイプ → Enter で確定 / Esc or Ctrl+C で終了"),
-        Line::from("Backspace で削除"),
+        Line::styled("Ratatui ECHO デモ", theme.title),
+        Line::from(format!(
+            "model: {} | max_tokens: {} | tools: {} | maxloops: {} | system: {system}",
+            s.model,
+            s.max_tokens,
+            format_tool_sets(&s.tool_sets),
+            s.max_loops
+        ))
+        .style(theme.tool),
+        Line::from(format!(
+            "{} 送信 / Shift+Enter 改行 / {} 終了 / {} 新しいタブ / {} ツール呼び出し / {} キー一覧 / /model /tools /maxloops /system",
+            keymap.describe(Action::Submit),
+            keymap.describe(Action::Quit),
+            keymap.describe(Action::NewSession),
+            keymap.describe(Action::TogglePanel),
+            keymap.describe(Action::Help)
+        ))
+        .style(theme.muted),
     ];
-    let guide_widget = Paragraph::new(guide)
-        .block(Block::default().borders(Borders::ALL).title("Guide"));
+    let guide_widget = Paragraph::new(guide).block(theme.block("Guide"));
     f.render_widget(guide_widget, area);
 }
 
 /// 入力欄を描画
 fn render_input(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let mut current = app.input.clone();
-    current.push('_'); // 簡易カーソル表示
-    let input_widget = Paragraph::new(current)
-        .block(Block::default().borders(Borders::ALL).title("Input"));
-    f.render_widget(input_widget, area);
+    app.input.render(f, area, app.theme.block("Input").style(app.theme.user));
 }
 
 /// 最後に送信されたテキストを描画
 fn render_last_submitted(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let submitted_widget = Paragraph::new(app.last_submitted.clone())
-        .block(Block::default().borders(Borders::ALL).title("Last Submitted"));
+    let submitted_widget = Paragraph::new(app.session().last_submitted.clone())
+        .style(app.theme.user)
+        .block(app.theme.block("Last Submitted"));
     f.render_widget(submitted_widget, area);
 }
 
-/// AI回答部分を描画
-fn render_ai_response(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let status = if app.pending {
-        "問い合わせ中...".to_string()
-    } else if let Some(ans) = &app.ai_answer {
-        ans.clone()
-    } else {
-        "(まだ回答はありません)".to_string()
+/// 待機キューを描画（選択中の項目は反転表示）
+fn render_queue(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let queue = &app.session().queue;
+    let selected = queue.selected();
+    let skip = queue_scroll(queue);
+    let lines: Vec<Line> = queue
+        .items()
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, prompt)| {
+            let first_line = prompt.lines().next().unwrap_or("");
+            let line = Line::from(format!("{}. {first_line}", i + 1));
+            if selected == Some(i) { line.style(app.theme.highlight) } else { line.style(app.theme.user) }
+        })
+        .collect();
+    let title = format!(
+        "Queue ({}) {} 選択 / {} 編集 / {} 削除",
+        queue.len(),
+        app.keymap.describe(Action::QueuePrev),
+        app.keymap.describe(Action::QueueEdit),
+        app.keymap.describe(Action::QueueRemove)
+    );
+    f.render_widget(Paragraph::new(lines).block(app.theme.block(title)), area);
+}
+
+/// AI回答部分を描画（Markdown をレンダリングし、折り返し・スクロール可能）
+///
+/// キュー処理中は直前の回答を表示したまま、タイトルで処理中であることを示す。
+fn render_ai_response(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let session = app.session_mut();
+    let (title, lines) = match (&session.ai_answer, session.pending) {
+        (Some(ans), true) => ("AI Answer（次のプロンプトを問い合わせ中...）", render_markdown(ans, &theme)),
+        (None, true) => ("AI Answer", vec![Line::styled("問い合わせ中...", theme.warning)]),
+        (Some(ans), false) => ("AI Answer", render_markdown(ans, &theme)),
+        (None, false) => ("AI Answer", vec![Line::styled("(まだ回答はありません)", theme.muted)]),
     };
-    let ai_widget = Paragraph::new(status)
-        .block(Block::default().borders(Borders::ALL).title("AI Answer"));
-    f.render_widget(ai_widget, area);
+    session.answer_view.render(f, area, title, lines, &theme);
 }
 
 /// フッター部分を描画
 fn render_footer(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let elapsed = app.started.elapsed().as_secs_f32();
-    let footer = Paragraph::new(Line::from(vec![Span::raw(format!(
-        "経過: {elapsed:.1}s"
-    ))]));
+    let mut spans = vec![Span::styled(format!("経過: {elapsed:.1}s"), app.theme.muted)];
+    if let Some(status) = &app.status {
+        // 送信エラーなどはエラーの色で表示
+        let style = if status.contains("エラー") { app.theme.error } else { app.theme.warning };
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(status.clone(), style));
+    }
+    let footer = Paragraph::new(Line::from(spans));
     f.render_widget(footer, area);
 }
+
+/// `area` の中央に幅 `width`・高さ `height` の矩形を取る
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height.min(area.height))]).flex(Flex::Center).areas(area);
+    let [cell] = Layout::horizontal([Constraint::Length(width.min(area.width))]).flex(Flex::Center).areas(row);
+    cell
+}
+
+/// キー一覧オーバーレイを描画
+fn render_help(f: &mut Frame, keymap: &Keymap, theme: &Theme) {
+    let mut lines: Vec<Line> = Action::ALL
+        .iter()
+        .map(|action| {
+            Line::from(vec![
+                Span::styled(format!("{:<22}", keymap.describe(*action)), theme.tool),
+                Span::raw(action.description()),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::styled("入力欄: ←→ 移動 / Ctrl+←→ 単語移動 / ↑↓ 入力履歴 / Shift+Enter 改行", theme.muted));
+    let area = centered(f.area(), 64, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(theme.block("Keys（任意のキーで閉じる）")), area);
+}
+
+/// 処理中の終了確認ダイアログを描画
+fn render_confirm_quit(f: &mut Frame, theme: &Theme) {
+    let area = centered(f.area(), 44, 4);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(vec![
+            Line::from("問い合わせ中です。終了しますか？"),
+            Line::styled("y: 終了 / その他のキー: 戻る", theme.muted),
+        ])
+        .block(theme.block("Quit").border_style(theme.warning)),
+        area,
+    );
+}
+
+/// 描画済みバッファを行ごとの文字列にする（全角文字の後ろの埋めセルは飛ばし、行末の空白は削る）
+pub fn buffer_lines(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+    let mut lines = Vec::with_capacity(area.height as usize);
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut x = area.left();
+        while x < area.right() {
+            let symbol = buffer[(x, y)].symbol();
+            line.push_str(symbol);
+            x += symbol.width().max(1) as u16;
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// 描画済みバッファを1つの文字列にする（テスト・スナップショット用）
+pub fn buffer_to_string(buffer: &Buffer) -> String {
+    buffer_lines(buffer).join("\n")
+}
@@ -0,0 +1,117 @@
+//! App が使うワーカーの差し替え
+//!
+//! 本番は OpenAI ワーカー、テストやデモでは OpenAI を呼ばないエコー / 台本ワーカーを使う。
+//! どのワーカーも `WorkerRequest` を受け取り、セッション ID 付きの `WorkerMessage` を返す。
+
+use std::collections::VecDeque;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::config::Config;
+use crate::openai::{self, MultiStepLogEvent, WorkerMessage, WorkerRequest};
+
+/// App のリクエストを処理するワーカー
+///
+/// 返信は tokio のチャンネルで送る（メインループが他のイベントと一緒に待つため）。
+pub trait Worker {
+    /// バックグラウンドでリクエストの受信を開始する（`requests` が閉じたら終了）
+    fn start(self: Box<Self>, requests: Receiver<WorkerRequest>, replies: UnboundedSender<WorkerMessage>);
+}
+
+/// OpenAI API を呼ぶワーカー（`openai::start_openai_worker`）
+pub struct OpenAiWorker {
+    config: Config,
+}
+
+impl OpenAiWorker {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+impl Worker for OpenAiWorker {
+    fn start(self: Box<Self>, requests: Receiver<WorkerRequest>, replies: UnboundedSender<WorkerMessage>) {
+        openai::start_openai_worker(requests, replies, self.config);
+    }
+}
+
+/// プロンプトをそのまま `echo: <prompt>` として返すワーカー
+pub struct EchoWorker;
+
+impl Worker for EchoWorker {
+    fn start(self: Box<Self>, requests: Receiver<WorkerRequest>, replies: UnboundedSender<WorkerMessage>) {
+        std::thread::spawn(move || {
+            while let Ok(request) = requests.recv() {
+                if let WorkerRequest::Prompt { session, prompt } = request {
+                    let _ = replies.send(WorkerMessage::Answer { session, answer: format!("echo: {prompt}") });
+                }
+            }
+        });
+    }
+}
+
+/// 台本の1ステップ（1つのプロンプトへの応答）
+#[derive(Debug, Clone, Default)]
+pub struct ScriptStep {
+    /// 回答の前に送る途中経過イベント
+    pub events: Vec<MultiStepLogEvent>,
+    /// 最終回答
+    pub answer: String,
+}
+
+/// 受け取ったプロンプトに台本の応答を順に返すワーカー
+///
+/// 台本が尽きたら `(台本の終わり)` と答える。受け取ったリクエストは `requests()` で確認できる。
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedWorker {
+    steps: VecDeque<ScriptStep>,
+    received: Arc<Mutex<Vec<WorkerRequest>>>,
+}
+
+impl ScriptedWorker {
+    /// 台本の最後の回答
+    pub const END_OF_SCRIPT: &'static str = "(台本の終わり)";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 次のプロンプトに `answer` と答える
+    pub fn answer(self, answer: impl Into<String>) -> Self {
+        self.step(Vec::new(), answer)
+    }
+
+    /// 次のプロンプトに `events` を送ってから `answer` と答える
+    pub fn step(mut self, events: Vec<MultiStepLogEvent>, answer: impl Into<String>) -> Self {
+        self.steps.push_back(ScriptStep { events, answer: answer.into() });
+        self
+    }
+
+    /// 受け取ったリクエストの記録（`start` 後も共有される）
+    pub fn requests(&self) -> Arc<Mutex<Vec<WorkerRequest>>> {
+        self.received.clone()
+    }
+}
+
+impl Worker for ScriptedWorker {
+    fn start(self: Box<Self>, requests: Receiver<WorkerRequest>, replies: UnboundedSender<WorkerMessage>) {
+        let ScriptedWorker { mut steps, received } = *self;
+        std::thread::spawn(move || {
+            while let Ok(request) = requests.recv() {
+                received.lock().expect("request log").push(request.clone());
+                let WorkerRequest::Prompt { session, .. } = request else { continue };
+                let step = steps.pop_front().unwrap_or_else(|| ScriptStep {
+                    events: Vec::new(),
+                    answer: Self::END_OF_SCRIPT.to_string(),
+                });
+                for event in step.events {
+                    let _ = replies.send(WorkerMessage::Event { session, at: Instant::now(), event });
+                }
+                let _ = replies.send(WorkerMessage::Answer { session, answer: step.answer });
+            }
+        });
+    }
+}
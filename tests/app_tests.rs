@@ -1,83 +1,331 @@
-use std::sync::mpsc::{channel, Sender, Receiver};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use rust_test::keymap::Action;
+use rust_test::openai::{MultiStepLogEvent, ToolCallDecision, WorkerMessage, WorkerRequest};
+use rust_test::theme::ThemeName;
+use rust_test::{event, ui, App, Config};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 
 mod common;
 #[ctor::ctor]
 fn _init() { common::init(); }
 
-/// A minimal app-like struct to test state transitions without spawning the OpenAI worker.
-struct TestApp {
-    pub input: String,
-    pub last_submitted: String,
-    pub ai_answer: Option<String>,
-    pub pending: bool,
-    pub tx: Sender<String>,
-    pub rx: Receiver<String>,
+/// App wired to plain channels: the test plays the worker.
+struct Harness {
+    app: App,
+    requests: Receiver<WorkerRequest>,
+    replies: UnboundedSender<WorkerMessage>,
 }
 
-impl TestApp {
+impl Harness {
     fn new() -> Self {
-        let (tx, rx_in) = channel::<String>();
-        let (tx_out, rx) = channel::<String>();
-
-        // Echo worker: echoes the prompt back after receiving it.
-        std::thread::spawn(move || {
-            while let Ok(msg) = rx_in.recv() {
-                let _ = tx_out.send(format!("echo: {}", msg));
-            }
-        });
-
-        Self {
-            input: String::new(),
-            last_submitted: String::from("(まだありません)"),
-            ai_answer: None,
-            pending: false,
-            tx,
-            rx,
-        }
+        Self::with_config(Config::new())
     }
 
-    fn submit_prompt(&mut self) {
-        if !self.input.is_empty() && !self.pending {
-            self.last_submitted = self.input.clone();
-            let to_send = self.input.clone();
-            self.input.clear();
-            self.ai_answer = None;
-            self.pending = true;
-            let _ = self.tx.send(to_send);
-        }
+    fn with_config(config: Config) -> Self {
+        let (tx, requests) = channel();
+        let (replies, rx) = unbounded_channel();
+        Self { app: App::with_channels(&config, tx, rx), requests, replies }
     }
 
-    fn check_ai_response(&mut self) {
-        if let Ok(ans) = self.rx.try_recv() {
-            self.ai_answer = Some(ans);
-            self.pending = false;
+    fn type_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            event::handle_key(&mut self.app, key(KeyCode::Char(ch))).unwrap();
         }
     }
+
+    fn submit(&mut self, text: &str) {
+        self.type_text(text);
+        assert!(!self.app.update(Action::Submit));
+    }
+
+    fn next_request(&self) -> WorkerRequest {
+        self.requests.try_recv().expect("request sent to worker")
+    }
+
+    fn answer(&mut self, session: u64, answer: &str) {
+        self.replies.send(WorkerMessage::Answer { session, answer: answer.into() }).unwrap();
+        self.app.check_ai_response();
+    }
+
+    fn render(&mut self) {
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        terminal.draw(|f| ui::render(f, &mut self.app)).unwrap();
+    }
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn tool_call(name: &str) -> WorkerMessage {
+    WorkerMessage::Event {
+        session: 0,
+        at: Instant::now(),
+        event: MultiStepLogEvent::Proposed {
+            iteration: 1,
+            decision: ToolCallDecision::ToolCall { name: name.into(), arguments: "{}".into() },
+        },
+    }
 }
 
 #[test]
 fn submit_and_receive_flow() {
-    let mut app = TestApp::new();
-    assert!(!app.pending);
-    assert!(app.ai_answer.is_none());
-
-    app.input = "hello".into();
-    app.submit_prompt();
-
-    // After submit, input cleared and pending true
-    assert_eq!(app.last_submitted, "hello");
-    assert_eq!(app.input, "");
-    assert!(app.pending);
-    assert!(app.ai_answer.is_none());
-
-    // Receive echo
-    // Spin a tiny wait loop to allow thread to process
-    for _ in 0..50 {
-        app.check_ai_response();
-        if app.ai_answer.is_some() { break; }
-        std::thread::sleep(std::time::Duration::from_millis(2));
+    let mut h = Harness::new();
+    assert!(!h.app.session().pending);
+    assert!(h.app.session().ai_answer.is_none());
+
+    h.submit("hello");
+    assert_eq!(h.app.session().last_submitted, "hello");
+    assert!(h.app.input.is_empty());
+    assert!(h.app.session().pending);
+    assert!(matches!(h.next_request(), WorkerRequest::Prompt { session: 0, prompt } if prompt == "hello"));
+
+    h.answer(0, "echo: hello");
+    assert!(!h.app.session().pending);
+    assert_eq!(h.app.session().ai_answer.as_deref(), Some("echo: hello"));
+}
+
+#[test]
+fn worker_failure_shows_the_error_and_ends_the_request() {
+    let mut h = Harness::new();
+    h.submit("hello");
+    h.next_request();
+    h.replies.send(WorkerMessage::Failed { session: 0, error: "401 invalid api key".into() }).unwrap();
+    h.app.check_ai_response();
+    assert!(!h.app.session().pending);
+    assert_eq!(h.app.status.as_deref(), Some("エラー: 401 invalid api key"));
+    assert!(h.app.session().ai_answer.as_deref().unwrap().contains("401 invalid api key"));
+}
+
+#[test]
+fn submit_send_error_is_shown() {
+    let (tx, requests) = channel();
+    let (_replies, rx) = unbounded_channel();
+    let mut app = App::with_channels(&Config::new(), tx, rx);
+    drop(requests); // ワーカーが終了している
+    app.paste("hello");
+    app.update(Action::Submit);
+    assert!(!app.session().pending);
+    assert!(app.status.as_deref().unwrap().starts_with("送信エラー"));
+    // 送れなかったプロンプトは入力欄に残り、送信済みとして扱われない
+    assert_eq!(app.input.text(), "hello");
+    assert_ne!(app.session().last_submitted, "hello");
+}
+
+#[test]
+fn queued_prompt_is_kept_when_the_worker_is_gone() {
+    let mut h = Harness::new();
+    h.submit("first");
+    h.submit("second");
+    assert_eq!(h.app.session().queue.len(), 1);
+
+    let Harness { mut app, requests, replies } = h;
+    drop(requests);
+    replies.send(WorkerMessage::Answer { session: 0, answer: "done".into() }).unwrap();
+    app.check_ai_response();
+    assert!(!app.session().pending);
+    assert_eq!(app.session().queue.items(), ["second"]);
+    assert_eq!(app.session().ai_answer.as_deref(), Some("done"));
+    assert!(app.status.as_deref().unwrap().starts_with("送信エラー"));
+}
+
+#[test]
+fn submit_slash_command_updates_settings() {
+    let mut h = Harness::new();
+    h.submit("/model gpt-4o");
+    assert_eq!(h.app.session().settings.model, "gpt-4o");
+    assert!(matches!(h.next_request(), WorkerRequest::UpdateSettings { session: 0, settings } if settings.model == "gpt-4o"));
+
+    // 解析エラーは入力を残す
+    h.submit("/nope");
+    assert_eq!(h.app.input.text(), "/nope");
+    assert!(h.app.status.as_deref().unwrap().starts_with("エラー"));
+}
+
+#[test]
+fn cancel_clears_input() {
+    let mut h = Harness::new();
+    h.type_text("draft");
+    h.app.update(Action::Cancel);
+    assert!(h.app.input.is_empty());
+}
+
+#[test]
+fn quit_confirms_while_pending() {
+    let mut h = Harness::new();
+    assert!(h.app.update(Action::Quit));
+
+    h.submit("hello");
+    assert!(!h.app.update(Action::Quit));
+    assert!(h.app.confirm_quit);
+    // 確認ダイアログでは割り当てのないキーが取り消しになる
+    assert!(!event::handle_key(&mut h.app, key(KeyCode::Char('n'))).unwrap());
+    assert!(!h.app.confirm_quit);
+
+    h.app.update(Action::Quit);
+    assert!(event::handle_key(&mut h.app, key(KeyCode::Char('y'))).unwrap());
+}
+
+#[test]
+fn scroll_and_page_move_answer() {
+    let mut h = Harness::new();
+    h.submit("long");
+    let long: Vec<String> = (0..100).map(|i| format!("line {i}")).collect();
+    h.answer(0, &long.join("\n\n"));
+    h.render();
+
+    h.app.update(Action::ScrollDown);
+    assert_eq!(h.app.session().answer_view.scroll(), 1);
+    h.app.update(Action::PageDown);
+    let paged = h.app.session().answer_view.scroll();
+    assert!(paged > 1);
+    h.app.update(Action::PageUp);
+    assert_eq!(h.app.session().answer_view.scroll(), 1);
+    h.app.update(Action::ScrollUp);
+    assert_eq!(h.app.session().answer_view.scroll(), 0);
+}
+
+#[test]
+fn panel_actions_only_apply_while_visible() {
+    let mut h = Harness::new();
+    h.submit("tools");
+    h.replies.send(tool_call("a")).unwrap();
+    h.replies.send(tool_call("b")).unwrap();
+    h.app.check_ai_response();
+    assert_eq!(h.app.session().inspector.entries().len(), 2);
+    let initial = h.app.session().inspector.selected();
+
+    h.app.update(Action::PanelNext);
+    h.app.update(Action::PanelPrev);
+    assert_eq!(h.app.session().inspector.selected(), initial);
+
+    h.app.update(Action::TogglePanel);
+    assert!(h.app.show_inspector);
+    h.app.update(Action::PanelPrev);
+    assert_eq!(h.app.session().inspector.selected(), 0);
+    h.app.update(Action::PanelNext);
+    assert_eq!(h.app.session().inspector.selected(), 1);
+    h.app.update(Action::PanelExpand);
+    assert!(h.app.session().inspector.is_expanded(1));
+}
+
+#[test]
+fn queue_actions_select_edit_and_remove() {
+    let mut h = Harness::new();
+    h.submit("first");
+    h.submit("second");
+    h.submit("third");
+    assert_eq!(h.app.session().queue.items(), ["second", "third"]);
+
+    h.app.update(Action::QueuePrev);
+    assert_eq!(h.app.session().queue.selected(), Some(1));
+    h.app.update(Action::QueueNext);
+    assert_eq!(h.app.session().queue.selected(), None);
+
+    h.app.update(Action::QueuePrev);
+    h.app.update(Action::QueuePrev);
+    h.app.update(Action::QueueEdit);
+    assert_eq!(h.app.input.text(), "second");
+    h.type_text("!");
+    h.app.update(Action::Submit);
+    assert_eq!(h.app.session().queue.items(), ["second!", "third"]);
+
+    h.app.update(Action::QueueNext);
+    assert_eq!(h.app.session().queue.selected(), Some(1));
+    h.app.update(Action::QueueRemove);
+    assert_eq!(h.app.session().queue.items(), ["second!"]);
+
+    // 回答が届くと次の待機プロンプトが送られる
+    h.answer(0, "done");
+    assert_eq!(h.app.session().last_submitted, "second!");
+    assert!(h.app.session().pending);
+}
+
+#[test]
+fn session_actions_open_switch_and_close_tabs() {
+    let mut h = Harness::new();
+    h.submit("in tab 1");
+    h.app.update(Action::NewSession);
+    assert_eq!(h.app.sessions.len(), 2);
+    assert_eq!(h.app.active, 1);
+    assert!(!h.app.session().pending);
+
+    h.app.update(Action::NextSession);
+    assert_eq!(h.app.active, 0);
+    h.app.update(Action::PrevSession);
+    assert_eq!(h.app.active, 1);
+
+    // 別タブ宛ての回答はそのタブに入る
+    h.answer(0, "answer 1");
+    assert!(h.app.session().ai_answer.is_none());
+    assert_eq!(h.app.sessions[0].ai_answer.as_deref(), Some("answer 1"));
+
+    h.app.update(Action::CloseSession);
+    assert_eq!(h.app.sessions.len(), 1);
+    assert_eq!(h.app.active, 0);
+    while let Ok(request) = h.requests.try_recv() {
+        if let WorkerRequest::CloseSession { session } = request {
+            assert_eq!(session, 1);
+            return;
+        }
     }
+    panic!("CloseSession not sent");
+}
 
-    assert!(!app.pending);
-    assert_eq!(app.ai_answer.as_deref(), Some("echo: hello"));
+#[test]
+fn help_opens_and_any_action_closes_it() {
+    let mut h = Harness::new();
+    h.app.update(Action::Help);
+    assert!(h.app.show_help);
+    h.type_text("x");
+    assert!(!h.app.show_help);
+    assert!(h.app.input.is_empty());
+}
+
+#[test]
+fn every_action_is_handled_without_panicking() {
+    let mut h = Harness::new();
+    for action in Action::ALL {
+        if action != Action::Quit {
+            assert!(!h.app.update(action), "{action} should not quit");
+        }
+    }
+}
+
+#[test]
+fn copy_actions_send_answer_and_code_blocks_to_clipboard() {
+    let mut h = Harness::new();
+    h.app.update(Action::CopyAnswer);
+    assert_eq!(h.app.take_pending_copy(), None);
+
+    h.submit("code");
+    h.answer(0, "two blocks:\n\n```sh\necho one\n```\n\n```rust\nfn two() {}\n```\n");
+    h.app.update(Action::CopyAnswer);
+    assert!(h.app.take_pending_copy().unwrap().starts_with("two blocks:"));
+
+    h.app.update(Action::CopyCodeBlock);
+    assert_eq!(h.app.take_pending_copy().as_deref(), Some("echo one"));
+    h.app.update(Action::CopyCodeBlock);
+    assert_eq!(h.app.take_pending_copy().as_deref(), Some("fn two() {}"));
+    assert_eq!(h.app.status.as_deref(), Some("コードブロック 2/2 をコピーしました（11文字）"));
+    h.app.update(Action::CopyCodeBlock);
+    assert_eq!(h.app.clipboard.as_deref(), Some("echo one"));
+}
+
+#[test]
+fn toggle_theme_cycles_configured_theme() {
+    let config = Config { theme: ThemeName::Light, ..Config::new() };
+    let mut h = Harness::with_config(config);
+    assert_eq!(h.app.theme.name, ThemeName::Light);
+    h.app.update(Action::ToggleTheme);
+    assert_eq!(h.app.theme.name, ThemeName::HighContrast);
+    assert!(h.app.status.as_deref().unwrap().starts_with("テーマ: high-contrast"));
+    h.app.update(Action::ToggleTheme);
+    assert_eq!(h.app.theme.name, ThemeName::Dark);
+    h.render();
 }
@@ -1,4 +1,5 @@
 use rust_test::config::{Config, X, Y};
+use rust_test::theme::ThemeName;
 mod common;
 
 #[ctor::ctor]
@@ -10,6 +11,7 @@ fn config_defaults() {
     assert_eq!(c.model, "gpt-4o-mini");
     assert_eq!(c.max_tokens, 2000);
     assert_eq!(c.poll_interval_ms, 100);
+    assert_eq!(c.theme, ThemeName::Dark);
 }
 
 #[test]
@@ -17,3 +19,34 @@ fn constants_values() {
     assert_eq!(X, 42);
     assert_eq!(Y, 7);
 }
+
+#[test]
+fn keymap_file_overrides_defaults() -> color_eyre::Result<()> {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use rust_test::keymap::Action;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("keymap.txt");
+    std::fs::write(&path, "# 終了は Ctrl+X だけ\nquit = ctrl+x\n")?;
+    let config = Config::new().with_keymap_file(&path)?;
+    let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+    assert_eq!(config.keymap.action_for(&ctrl('x')), Some(Action::Quit));
+    assert_eq!(config.keymap.action_for(&ctrl('q')), None);
+    Ok(())
+}
+
+#[test]
+fn bad_keymap_file_is_an_error() -> color_eyre::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("keymap.txt");
+    std::fs::write(&path, "quit = ctrl+x\nlaunch = f9\n")?;
+    let err = Config::new().with_keymap_file(&path).err().expect("unknown action must fail");
+    let report = format!("{err:?}");
+    assert!(report.contains("invalid keymap file"), "{report}");
+    assert!(report.contains("line 2: unknown action `launch`"), "{report}");
+
+    std::fs::write(&path, "quit = hyper+x\n")?;
+    assert!(Config::new().with_keymap_file(&path).is_err());
+    assert!(Config::new().with_keymap_file(dir.path().join("missing.txt")).is_err());
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use rust_test::mcp::{build_mcp_tools, start_mcp_tools, McpClient, McpServerConfig};
+use rust_test::openai::{build_add_tool, resolve_and_execute_tool_call, ToolCallDecision, ToolResolution};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+mod common;
+
+const FIXTURE_ENV: &str = "MCP_FIXTURE_SERVER";
+/// Makes the fixture read requests but never answer them
+const HANG_ENV: &str = "MCP_FIXTURE_HANG";
+
+#[ctor::ctor]
+fn _init() {
+    // When re-executed as the fixture, serve MCP on stdio and exit before libtest prints anything.
+    if std::env::var(FIXTURE_ENV).is_ok() {
+        fixture_server();
+        std::process::exit(0);
+    }
+    common::init();
+}
+
+/// Minimal hand-written stdio MCP server used as a third-party fixture.
+/// Tools: `echo` (returns its arguments), `shout` (plain text result), `explode` (isError).
+fn fixture_server() {
+    let stdin = std::io::stdin();
+    let mut out = std::io::stdout();
+    let hang = std::env::var(HANG_ENV).is_ok();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        if hang { continue; }
+        let Ok(req) = serde_json::from_str::<Value>(&line) else { continue };
+        let Some(id) = req.get("id").cloned() else { continue }; // notification
+        let result = match req["method"].as_str().unwrap_or("") {
+            "initialize" => json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "fixture", "version": "0.0.1"}
+            }),
+            "tools/list" => json!({"tools": [
+                {"name": "echo", "description": "Echo arguments back",
+                 "inputSchema": {"type": "object", "properties": {"msg": {"type": "string"}}, "required": ["msg"]}},
+                {"name": "shout", "description": "Uppercase text",
+                 "inputSchema": {"type": "object", "properties": {"msg": {"type": "string"}}}},
+                {"name": "explode", "description": "Always fails", "inputSchema": {"type": "object"}}
+            ]}),
+            "tools/call" => {
+                let args = &req["params"]["arguments"];
+                match req["params"]["name"].as_str().unwrap_or("") {
+                    "echo" => json!({"content": [{"type": "text", "text": args.to_string()}], "isError": false}),
+                    "shout" => {
+                        let msg = args["msg"].as_str().unwrap_or("").to_uppercase();
+                        json!({"content": [{"type": "text", "text": msg}]})
+                    }
+                    _ => json!({"content": [{"type": "text", "text": "boom"}], "isError": true}),
+                }
+            }
+            _ => {
+                let resp = json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32601, "message": "nope"}});
+                let _ = writeln!(out, "{resp}");
+                let _ = out.flush();
+                continue;
+            }
+        };
+        // A server-initiated notification before each response; the client must skip it.
+        let _ = writeln!(out, "{}", json!({"jsonrpc": "2.0", "method": "notifications/message", "params": {}}));
+        let _ = writeln!(out, "{}", json!({"jsonrpc": "2.0", "id": id, "result": result}));
+        let _ = out.flush();
+    }
+}
+
+fn fixture_config() -> McpServerConfig {
+    let exe = std::env::current_exe().expect("test exe path");
+    McpServerConfig::new("fixture", exe.to_string_lossy()).env(FIXTURE_ENV, "1")
+}
+
+#[test]
+fn lists_and_calls_remote_tools() -> color_eyre::Result<()> {
+    let client = McpClient::spawn(&fixture_config())?;
+    let remote = client.list_tools()?;
+    let names: Vec<&str> = remote.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["echo", "shout", "explode"]);
+
+    assert_eq!(client.call_tool("echo", &json!({"msg": "hi"}))?["msg"], "hi");
+    assert_eq!(client.call_tool("shout", &json!({"msg": "hi"}))?["text"], "HI");
+    assert!(client.call_tool("explode", &json!({})).is_err());
+    assert!(client.request("resources/list", None).is_err());
+    Ok(())
+}
+
+#[test]
+fn wrapped_tools_work_in_resolver_alongside_builtin() -> color_eyre::Result<()> {
+    let client = McpClient::spawn(&fixture_config())?;
+    let mut tools = vec![build_add_tool()];
+    tools.extend(build_mcp_tools(&client)?);
+    assert_eq!(tools[1].as_chat_tool().function.name, "fixture__echo");
+
+    let call = |name: &str, args: Value| {
+        resolve_and_execute_tool_call(
+            ToolCallDecision::ToolCall { name: name.into(), arguments: args.to_string() },
+            &tools,
+        )
+    };
+    assert_eq!(call("add", json!({"x": 1, "y": 2})), ToolResolution::Executed { name: "add".into(), result: json!({"sum": 3}) });
+    assert_eq!(
+        call("fixture__echo", json!({"msg": "yo"})),
+        ToolResolution::Executed { name: "fixture__echo".into(), result: json!({"msg": "yo"}) }
+    );
+    assert!(matches!(call("fixture__explode", json!({})), ToolResolution::ExecutionError { .. }));
+    Ok(())
+}
+
+#[test]
+fn broken_server_is_skipped() {
+    let tools = start_mcp_tools(&[
+        McpServerConfig::new("missing", "definitely-not-a-real-mcp-binary"),
+        fixture_config(),
+    ]);
+    assert_eq!(tools.len(), 3);
+}
+
+#[test]
+fn remote_tools_do_not_shadow_builtin_names() -> color_eyre::Result<()> {
+    // Same remote tool set from two servers: every name stays unique and none is a built-in name
+    let a = McpClient::spawn(&fixture_config())?;
+    let b = McpClient::spawn(&McpServerConfig { name: "other".into(), ..fixture_config() })?;
+    let mut names: Vec<String> = build_mcp_tools(&a)?.into_iter().chain(build_mcp_tools(&b)?).map(|t| t.name).collect();
+    assert!(names.contains(&"other__echo".to_string()));
+    let total = names.len();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), total);
+    assert!(!names.iter().any(|n| n == "echo"));
+    Ok(())
+}
+
+#[test]
+fn hanging_server_times_out_and_is_skipped() {
+    let hanging = fixture_config().env(HANG_ENV, "1").timeout(Duration::from_millis(200));
+    let started = Instant::now();
+    let err = McpClient::spawn(&hanging).unwrap_err();
+    assert!(err.to_string().contains("did not answer initialize"), "{err}");
+
+    let tools = start_mcp_tools(&[hanging, fixture_config()]);
+    assert_eq!(tools.len(), 3);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
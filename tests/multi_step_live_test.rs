@@ -20,6 +20,7 @@ fn skip_if_no_api_key() -> bool {
 /// 1) Model calls `get_constants` to retrieve `{ X, Y }` (provided by config constants)
 /// 2) Model calls `add` with those numbers to compute the sum
 /// 3) Model returns a concise Japanese explanation including the result
+///
 /// Ignored by default. Run with: `cargo test --test multi_step_read_docs_live_test -- --ignored`
 #[test]
 #[ignore]
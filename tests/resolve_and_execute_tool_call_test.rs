@@ -27,7 +27,7 @@ fn live_tool_call_with_tavily_search() -> Result<(), Box<dyn std::error::Error>>
     let cfg = Config::new();
     let prompt = "1+2";
     let history: [async_openai::types::ChatCompletionRequestMessage; 0] = [];
-    let decision = propose_tool_call_blocking(&history, prompt, &[tool_def.clone()], &cfg)?;
+    let decision = propose_tool_call_blocking(&history, prompt, std::slice::from_ref(&tool_def), &cfg)?;
     tracing::info!(target="live_test", decision=?decision, "tavily tool decision");
 
     let tool_results: ToolResolution = resolve_and_execute_tool_call(decision, &[tool_def]);
@@ -0,0 +1,101 @@
+//! Replays of seeded `rpg::Game`s: the same seed and commands must give exactly the same battle.
+
+use rust_test::rpg::{Command, Game, GameEvent, Turn, DEFAULT_PLAYER_NAME};
+use serde_json::json;
+
+mod common;
+#[ctor::ctor]
+fn _init() { common::init(); }
+
+const SEED: u64 = 42;
+
+/// Run `commands` on a fresh game and return every event plus the final game
+fn replay(seed: u64, commands: &[Command]) -> (Vec<GameEvent>, Game) {
+    let mut game = Game::with_seed(seed);
+    let events = commands.iter().flat_map(|&c| game.handle_command(c)).collect();
+    (events, game)
+}
+
+fn dealt(target: &str, damage: i32) -> GameEvent {
+    GameEvent::DamageDealt { target: target.into(), damage }
+}
+
+fn taken(attacker: &str, damage: i32) -> GameEvent {
+    GameEvent::DamageTaken { attacker: attacker.into(), damage }
+}
+
+#[test]
+fn seeded_game_starts_the_same_way() {
+    let game = Game::with_seed(SEED);
+    let snapshot = game.snapshot();
+    assert_eq!(snapshot.seed, SEED);
+    // Seeded games ignore RPG_PLAYER, so the snapshot does not depend on the environment
+    assert_eq!(snapshot.player.name, DEFAULT_PLAYER_NAME);
+    assert_eq!(snapshot.enemy.name, "Goblin");
+    assert_eq!(snapshot.turn, Turn::Enemy);
+    assert_eq!(serde_json::to_value(&snapshot).unwrap()["seed"], json!(SEED));
+}
+
+#[test]
+fn fixed_command_sequence_has_exact_outcome() {
+    use Command::*;
+    let (events, game) = replay(SEED, &[Attack, Attack, Heal, Attack, Run, Attack]);
+    assert_eq!(
+        events,
+        [
+            dealt("Goblin", 7),
+            taken("Goblin", 4),
+            dealt("Goblin", 4),
+            taken("Goblin", 4),
+            GameEvent::Healed { amount: 9 },
+            taken("Goblin", 5),
+            dealt("Goblin", 4),
+            taken("Goblin", 5),
+            GameEvent::FleeFailed,
+            taken("Goblin", 4),
+            dealt("Goblin", 7),
+            GameEvent::EnemyDefeated { name: "Goblin".into() },
+            GameEvent::Loot { gold: 9, potion: false },
+            GameEvent::EnemyAppeared { name: "Wolf".into(), hp: 22 },
+        ]
+    );
+    let player = game.player();
+    assert_eq!((player.hp, player.potions, player.gold), (16, 1, 9));
+    assert_eq!((game.enemy().name.as_str(), game.enemy().hp), ("Wolf", 22));
+    assert_eq!(game.battle_count(), 2);
+}
+
+#[test]
+fn same_seed_replays_identically_and_other_seeds_differ() {
+    let commands = [Command::Attack, Command::Run, Command::Heal, Command::Attack, Command::Attack, Command::Run];
+    let (events_a, game_a) = replay(SEED, &commands);
+    let (events_b, game_b) = replay(SEED, &commands);
+    assert_eq!(events_a, events_b);
+    assert_eq!(serde_json::to_value(game_a.snapshot()).unwrap(), serde_json::to_value(game_b.snapshot()).unwrap());
+
+    let (events_c, _) = replay(SEED + 1, &commands);
+    assert_ne!(events_a, events_c);
+}
+
+#[test]
+fn attacking_forever_ends_in_the_same_defeat() {
+    let mut game = Game::with_seed(SEED);
+    let mut events = Vec::new();
+    while !game.is_over() {
+        events.extend(game.handle_command(Command::Attack));
+    }
+    assert_eq!(events.last(), Some(&GameEvent::Defeat));
+    assert_eq!(game.battle_count(), 3);
+    assert_eq!(game.player().gold, 15);
+    assert_eq!(events.iter().filter(|e| matches!(e, GameEvent::DamageDealt { .. })).count(), 9);
+}
+
+#[test]
+fn renaming_the_player_does_not_change_the_replay() {
+    let commands = [Command::Attack, Command::Attack, Command::Heal, Command::Run];
+    let (events, _) = replay(SEED, &commands);
+    let mut named = Game::with_seed(SEED).with_player_name("Alice");
+    let named_events: Vec<GameEvent> = commands.iter().flat_map(|&c| named.handle_command(c)).collect();
+    assert_eq!(named.player().name, "Alice");
+    assert_eq!(events, named_events);
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use futures::stream::{self, Stream};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use rust_test::openai::{MultiStepLogEvent, ToolCallDecision, ToolResolution, WorkerRequest};
+use rust_test::worker::{EchoWorker, ScriptedWorker};
+use rust_test::{event, run_loop, ui, App, Config};
+use serde_json::json;
+
+mod common;
+#[ctor::ctor]
+fn _init() { common::init(); }
+
+fn press(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> bool {
+    event::handle_key(app, KeyEvent::new(code, modifiers)).unwrap()
+}
+
+fn type_and_enter(app: &mut App, text: &str) {
+    for ch in text.chars() {
+        press(app, KeyCode::Char(ch), KeyModifiers::NONE);
+    }
+    press(app, KeyCode::Enter, KeyModifiers::NONE);
+}
+
+/// 表示中のタブの回答が届くまで待つ
+fn wait_for_answer(app: &mut App) {
+    for _ in 0..200 {
+        app.check_ai_response();
+        if !app.session().pending {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("worker did not answer");
+}
+
+/// 描画した画面を行ごとの文字列にする
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| ui::render(f, app)).unwrap();
+    ui::buffer_to_string(terminal.backend().buffer())
+}
+
+#[test]
+fn echo_worker_round_trip_is_rendered() {
+    let mut app = App::with_worker(&Config::new(), Box::new(EchoWorker));
+    type_and_enter(&mut app, "hello");
+    assert!(render(&mut app, 80, 24).contains("問い合わせ中..."));
+
+    wait_for_answer(&mut app);
+    let screen = render(&mut app, 80, 24);
+    assert!(screen.contains("echo: hello"), "{screen}");
+    assert!(screen.contains("1 hello [idle]"), "{screen}");
+}
+
+#[test]
+fn scripted_tool_calls_show_in_inspector() {
+    let events = vec![
+        MultiStepLogEvent::Proposed {
+            iteration: 1,
+            decision: ToolCallDecision::ToolCall { name: "get_x".into(), arguments: "{}".into() },
+        },
+        MultiStepLogEvent::Resolved {
+            iteration: 1,
+            resolution: ToolResolution::Executed { name: "get_x".into(), result: json!({"x": 42}) },
+        },
+    ];
+    let worker = ScriptedWorker::new().step(events, "X は 42 です");
+    let mut app = App::with_worker(&Config::new(), Box::new(worker));
+
+    type_and_enter(&mut app, "X は？");
+    wait_for_answer(&mut app);
+    press(&mut app, KeyCode::F(2), KeyModifiers::NONE);
+
+    let screen = render(&mut app, 120, 30);
+    assert!(screen.contains("X は 42 です"), "{screen}");
+    assert!(screen.contains("get_x"), "{screen}");
+}
+
+#[test]
+fn scripted_worker_records_session_requests() {
+    let worker = ScriptedWorker::new().answer("first").answer("second");
+    let received = worker.requests();
+    let mut app = App::with_worker(&Config::new(), Box::new(worker));
+
+    type_and_enter(&mut app, "/model gpt-4o");
+    type_and_enter(&mut app, "one");
+    wait_for_answer(&mut app);
+    press(&mut app, KeyCode::Char('n'), KeyModifiers::CONTROL);
+    type_and_enter(&mut app, "two");
+    wait_for_answer(&mut app);
+    type_and_enter(&mut app, "three");
+    wait_for_answer(&mut app);
+
+    assert_eq!(app.sessions[0].ai_answer.as_deref(), Some("first"));
+    assert_eq!(app.session().ai_answer.as_deref(), Some(ScriptedWorker::END_OF_SCRIPT));
+    let prompts: Vec<(u64, String)> = received
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|r| match r {
+            WorkerRequest::Prompt { session, prompt } => Some((*session, prompt.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(prompts, [(0, "one".into()), (1, "two".into()), (1, "three".into())]);
+    assert!(matches!(received.lock().unwrap()[0], WorkerRequest::UpdateSettings { session: 0, .. }));
+}
+
+fn mouse(app: &mut App, kind: MouseEventKind, column: u16, row: u16) {
+    event::handle_mouse(app, MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE });
+}
+
+#[test]
+fn mouse_selects_answer_text_and_pastes_it() {
+    let mut app = App::with_worker(&Config::new(), Box::new(EchoWorker));
+    type_and_enter(&mut app, "hello world");
+    wait_for_answer(&mut app);
+    render(&mut app, 80, 24);
+
+    // 回答ペインの1行目「echo: hello world」の "hello" をドラッグで選択
+    let answer = app.panes.answer;
+    let (x, y) = (answer.x + 1, answer.y + 1);
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), x + 6, y);
+    render(&mut app, 80, 24);
+    mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), x + 10, y);
+    let screen = render(&mut app, 80, 24);
+    mouse(&mut app, MouseEventKind::Up(MouseButton::Left), x + 10, y);
+    assert_eq!(app.clipboard.as_deref(), Some("hello"), "{screen}");
+    assert_eq!(app.take_pending_copy().as_deref(), Some("hello"));
+
+    // 中クリックで入力欄へ貼り付け
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Middle), 0, 0);
+    assert_eq!(app.input.text(), "hello");
+}
+
+#[test]
+fn mouse_clicks_switch_tabs_and_move_cursor() {
+    let mut app = App::with_worker(&Config::new(), Box::new(EchoWorker));
+    press(&mut app, KeyCode::Char('n'), KeyModifiers::CONTROL);
+    assert_eq!(app.active, 1);
+    render(&mut app, 80, 24);
+
+    // " 1 new [idle] | 2 new [idle]" の1つ目・2つ目
+    let tabs_y = app.panes.tabs.y;
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 3, tabs_y);
+    assert_eq!(app.active, 0);
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 18, tabs_y);
+    assert_eq!(app.active, 1);
+
+    for ch in "abcdef".chars() {
+        press(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
+    }
+    render(&mut app, 80, 24);
+    let input = app.panes.input;
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), input.x + 3, input.y + 1);
+    assert_eq!(app.input.cursor(), 2);
+}
+
+/// `delay` ごとに1つずつイベントを流す端末入力の代わり
+fn scripted_events(events: Vec<(Duration, Event)>) -> impl Stream<Item = std::io::Result<Event>> + Unpin {
+    Box::pin(stream::unfold(events.into_iter(), |mut events| async move {
+        let (delay, event) = events.next()?;
+        tokio::time::sleep(delay).await;
+        Some((Ok(event), events))
+    }))
+}
+
+#[tokio::test]
+async fn event_loop_redraws_only_on_changes() {
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+    let short = Duration::from_millis(5);
+    let events = scripted_events(vec![
+        (short, key(KeyCode::Char('h'))),
+        (short, key(KeyCode::Char('i'))),
+        (short, key(KeyCode::Enter)),
+        // 回答が届いた後は何もしない（タイマーでは再描画しない）
+        (Duration::from_millis(500), Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL))),
+    ]);
+    let mut app = App::with_worker(&Config::new(), Box::new(EchoWorker));
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+    let frames = run_loop(&mut terminal, &mut app, events, Duration::from_millis(10)).await.unwrap();
+
+    assert_eq!(app.session().ai_answer.as_deref(), Some("echo: hi"));
+    // 初回 + キー3回 + 回答 (+ 回答待ちのタイマー数回)。待機中の 500ms で 50 回描くことはない
+    assert!(frames < 15, "drew {frames} frames");
+}
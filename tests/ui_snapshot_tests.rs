@@ -0,0 +1,138 @@
+//! Snapshot tests of `ui::render`.
+//!
+//! Each test renders an `App` state into a `TestBackend` and compares it with
+//! `tests/snapshots/<name>.txt`. Run with `UPDATE_SNAPSHOTS=1` to (re)write the
+//! snapshot files after an intended layout change, then review the diff.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use rust_test::keymap::Action;
+use rust_test::openai::{WorkerMessage, WorkerRequest};
+use rust_test::{event, ui, App, Config};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+mod common;
+#[ctor::ctor]
+fn _init() { common::init(); }
+
+/// 書き換えを有効にする環境変数
+const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+/// App with the test acting as the worker (no threads, fully deterministic).
+///
+/// The request receiver is returned so that sends keep succeeding while the test runs.
+fn app() -> (App, UnboundedSender<WorkerMessage>, Receiver<WorkerRequest>) {
+    let (tx, requests) = channel();
+    let (replies, rx) = unbounded_channel();
+    (App::with_channels(&Config::new(), tx, rx), replies, requests)
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for ch in text.chars() {
+        event::handle_key(app, KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)).unwrap();
+    }
+}
+
+fn submit(app: &mut App, text: &str) {
+    type_text(app, text);
+    app.update(Action::Submit);
+}
+
+fn answer(app: &mut App, replies: &UnboundedSender<WorkerMessage>, text: &str) {
+    replies.send(WorkerMessage::Answer { session: 0, answer: text.into() }).unwrap();
+    app.check_ai_response();
+}
+
+fn render(app: &mut App) -> String {
+    render_sized(app, WIDTH, HEIGHT)
+}
+
+fn render_sized(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    // 経過時間の表示を固定する
+    app.started = Instant::now();
+    terminal.draw(|f| ui::render(f, app)).unwrap();
+    ui::buffer_to_string(terminal.backend().buffer())
+}
+
+/// `tests/snapshots/<name>.txt` と比較する（`UPDATE_SNAPSHOTS` が設定されていれば書き換える）
+fn assert_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", &format!("{name}.txt")].iter().collect();
+    let actual = format!("{actual}\n");
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {} (run with {UPDATE_ENV}=1)", path.display()));
+    assert!(
+        expected == actual,
+        "snapshot `{name}` differs (run with {UPDATE_ENV}=1 to update)\n--- expected\n{expected}\n--- actual\n{actual}"
+    );
+}
+
+#[test]
+fn snapshot_empty() {
+    let (mut app, _replies, _requests) = app();
+    assert_snapshot("empty", &render(&mut app));
+}
+
+#[test]
+fn snapshot_pending() {
+    let (mut app, _replies, _requests) = app();
+    submit(&mut app, "X の値は？");
+    type_text(&mut app, "next");
+    assert_snapshot("pending", &render(&mut app));
+}
+
+#[test]
+fn snapshot_answered() {
+    let (mut app, replies, _requests) = app();
+    submit(&mut app, "X の値は？");
+    answer(&mut app, &replies, "# 結果\n\nX は **42** です。\n\n- get_x を呼びました\n- `x = 42`");
+    assert_snapshot("answered", &render(&mut app));
+}
+
+#[test]
+fn snapshot_error() {
+    let (mut app, _replies, _requests) = app();
+    submit(&mut app, "/frobnicate");
+    assert_snapshot("error", &render(&mut app));
+}
+
+#[test]
+fn snapshot_long_answer_scrolled() {
+    let (mut app, replies, _requests) = app();
+    submit(&mut app, "long");
+    let long: Vec<String> = (1..=40).map(|i| format!("{i}. 項目 {i}")).collect();
+    answer(&mut app, &replies, &long.join("\n"));
+    render(&mut app); // スクロール範囲を確定させる
+    app.update(Action::PageDown);
+    assert_snapshot("long_answer", &render(&mut app));
+}
+
+#[test]
+fn snapshot_compact_terminal() {
+    let (mut app, replies, _requests) = app();
+    submit(&mut app, "X の値は？");
+    answer(&mut app, &replies, "X は 42 です。");
+    assert_snapshot("compact", &render_sized(&mut app, 50, 14));
+}
+
+#[test]
+fn snapshot_wide_terminal_with_inspector() {
+    let (mut app, replies, _requests) = app();
+    submit(&mut app, "X の値は？");
+    answer(&mut app, &replies, "X は 42 です。");
+    app.update(Action::TogglePanel);
+    assert_snapshot("wide", &render_sized(&mut app, 150, 20));
+}